@@ -0,0 +1,304 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{Cell, CellSlice, JsonValue};
+use crate::error::*;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Function identifiers calculated from ABI
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct FunctionIds {
+    pub name: String,
+    pub input_id: u32,
+    pub output_id: u32,
+}
+
+/// Event identifier calculated from ABI
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct EventId {
+    pub name: String,
+    pub id: u32,
+}
+
+/// Function or event parameter described in ABI
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiParam {
+    pub name: String,
+    pub param_type: String,
+    /// Tuple components. Empty for other types
+    pub components: Vec<AbiParam>,
+}
+
+impl AbiParam {
+    /// Type used in signatures. Tuples are rendered as components list, e.g. `(uint256,address)[]`
+    pub fn type_signature(&self) -> String {
+        match self.param_type.strip_prefix("tuple") {
+            Some(array_suffix) => format!("({}){}", params_list(&self.components), array_suffix),
+            None => self.param_type.clone()
+        }
+    }
+
+    fn from_value(param: &Value) -> TonResult<Self> {
+        let param_type = param["type"].as_str()
+            .ok_or_else(|| TonErrorKind::InvalidData(format!("ABI param has no type: {}", param)))?;
+        let components = if param_type.starts_with("tuple") {
+            param["components"].as_array()
+                .ok_or_else(|| TonErrorKind::InvalidData(format!("ABI tuple has no components: {}", param)))?
+                .iter()
+                .map(AbiParam::from_value)
+                .collect::<TonResult<Vec<_>>>()?
+        } else {
+            vec![]
+        };
+        Ok(AbiParam {
+            name: param["name"].as_str().unwrap_or_default().to_owned(),
+            param_type: param_type.to_owned(),
+            components,
+        })
+    }
+}
+
+/// Contract function described in ABI
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiFunction {
+    pub name: String,
+    pub inputs: Vec<AbiParam>,
+    pub outputs: Vec<AbiParam>,
+    /// Id of function call message body
+    pub input_id: u32,
+    /// Id of function response message body
+    pub output_id: u32,
+}
+
+impl AbiFunction {
+    /// Human-readable signature, e.g. `subscribe(uint256,uint256,address,uint64,uint32)`
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, params_list(&self.inputs))
+    }
+
+    /// Signature with outputs which is hashed to calculate function id,
+    /// e.g. `getWallet()(address)v2`
+    pub fn full_signature(&self, version: u8) -> String {
+        format!("{}({})({})v{}", self.name, params_list(&self.inputs), params_list(&self.outputs), version)
+    }
+}
+
+/// Contract event described in ABI
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiEvent {
+    pub name: String,
+    pub inputs: Vec<AbiParam>,
+    pub id: u32,
+}
+
+impl AbiEvent {
+    /// Human-readable signature, e.g. `TransferAccepted(bytes)`
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, params_list(&self.inputs))
+    }
+}
+
+/// Functions, events and header parameters of contract ABI with calculated ids
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiInfo {
+    pub version: u8,
+    /// Header parameters of external inbound messages
+    pub header: Vec<String>,
+    pub functions: Vec<AbiFunction>,
+    pub events: Vec<AbiEvent>,
+}
+
+impl AbiInfo {
+    pub fn parse(abi: JsonValue) -> TonResult<Self> {
+        Self::from_value(&abi.to_value()?)
+    }
+
+    pub(crate) fn from_value(abi: &Value) -> TonResult<Self> {
+        let version = abi_version(abi)?;
+
+        let functions = abi_items(abi, "functions")
+            .map(|function| {
+                let (input_id, output_id) = function_id_pair(function, version)?;
+                Ok(AbiFunction {
+                    name: function_name(function)?.to_owned(),
+                    inputs: abi_params(&function["inputs"])?,
+                    outputs: abi_params(&function["outputs"])?,
+                    input_id,
+                    output_id,
+                })
+            })
+            .collect::<TonResult<Vec<_>>>()?;
+
+        let events = abi_items(abi, "events")
+            .map(|event| Ok(AbiEvent {
+                name: function_name(event)?.to_owned(),
+                inputs: abi_params(&event["inputs"])?,
+                id: event_id(event, version)?,
+            }))
+            .collect::<TonResult<Vec<_>>>()?;
+
+        Ok(AbiInfo { version, header: abi_header(abi)?, functions, events })
+    }
+
+    pub fn function(&self, name: &str) -> Option<&AbiFunction> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+
+    pub fn event(&self, name: &str) -> Option<&AbiEvent> {
+        self.events.iter().find(|event| event.name == name)
+    }
+
+    /// Function which call (input) or response (output) id equals `id`
+    pub fn function_by_id(&self, id: u32) -> Option<&AbiFunction> {
+        self.functions.iter().find(|function| function.input_id == id || function.output_id == id)
+    }
+
+    pub fn event_by_id(&self, id: u32) -> Option<&AbiEvent> {
+        self.events.iter().find(|event| event.id == id)
+    }
+}
+
+fn params_list(params: &[AbiParam]) -> String {
+    params.iter().map(AbiParam::type_signature).collect::<Vec<_>>().join(",")
+}
+
+fn abi_params(params: &Value) -> TonResult<Vec<AbiParam>> {
+    params.as_array()
+        .map(|params| params.iter().map(AbiParam::from_value).collect())
+        .unwrap_or_else(|| Ok(vec![]))
+}
+
+fn abi_items<'a>(abi: &'a Value, kind: &str) -> impl Iterator<Item = &'a Value> {
+    abi[kind].as_array().map(|items| items.iter()).into_iter().flatten()
+}
+
+pub(crate) fn abi_version(abi: &Value) -> TonResult<u8> {
+    abi["ABI version"].as_u64()
+        .filter(|version| *version == 1 || *version == 2)
+        .map(|version| version as u8)
+        .ok_or_else(|| TonErrorKind::InvalidData("Unsupported or missing ABI version".to_owned()).into())
+}
+
+/// Header parameters stored in external inbound message body before function id
+pub(crate) fn abi_header(abi: &Value) -> TonResult<Vec<String>> {
+    if abi_version(abi)? == 1 {
+        // ABI v1 stores message creation time unless `setTime` is disabled
+        return Ok(if abi["setTime"].as_bool().unwrap_or(true) { vec!["time".to_owned()] } else { vec![] });
+    }
+    abi["header"].as_array()
+        .map(|header| header.iter()
+            .map(|param| param.as_str()
+                .or_else(|| param["name"].as_str())
+                .map(|name| name.to_owned())
+                .ok_or_else(|| TonErrorKind::InvalidData(format!("Invalid ABI header param: {}", param)).into()))
+            .collect())
+        .unwrap_or_else(|| Ok(vec![]))
+}
+
+fn params_signature(params: &Value) -> TonResult<String> {
+    Ok(params_list(&abi_params(params)?))
+}
+
+pub(crate) fn function_signature(function: &Value, version: u8) -> TonResult<String> {
+    Ok(format!(
+        "{}({})({})v{}",
+        function_name(function)?,
+        params_signature(&function["inputs"])?,
+        params_signature(&function["outputs"])?,
+        version))
+}
+
+pub(crate) fn event_signature(event: &Value, version: u8) -> TonResult<String> {
+    Ok(format!("{}({})v{}", function_name(event)?, params_signature(&event["inputs"])?, version))
+}
+
+/// First 4 bytes of signature SHA256 hash
+pub(crate) fn signature_id(signature: &str) -> u32 {
+    let hash = Sha256::digest(signature.as_bytes());
+    u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
+}
+
+fn function_name(function: &Value) -> TonResult<&str> {
+    function["name"].as_str()
+        .ok_or_else(|| TonErrorKind::InvalidData(format!("ABI item has no name: {}", function)).into())
+}
+
+// explicit `id` set in ABI overrides calculated one
+fn explicit_id(item: &Value) -> TonResult<Option<u32>> {
+    let id = match &item["id"] {
+        Value::Null => return Ok(None),
+        Value::Number(id) => id.as_u64().map(|id| id as u32),
+        Value::String(id) => match id.strip_prefix("0x") {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => id.parse().ok()
+        },
+        _ => None
+    };
+    id.map(Some)
+        .ok_or_else(|| TonErrorKind::InvalidData(format!("Invalid ABI item id: {}", item["id"])).into())
+}
+
+// function call id has the highest bit cleared, response id has it set
+fn function_id_pair(function: &Value, version: u8) -> TonResult<(u32, u32)> {
+    Ok(match explicit_id(function)? {
+        Some(id) => (id, id),
+        None => {
+            let id = signature_id(&function_signature(function, version)?);
+            (id & 0x7FFFFFFF, id | 0x80000000)
+        }
+    })
+}
+
+fn event_id(event: &Value, version: u8) -> TonResult<u32> {
+    Ok(match explicit_id(event)? {
+        Some(id) => id,
+        None => signature_id(&event_signature(event, version)?) & 0x7FFFFFFF
+    })
+}
+
+pub(crate) fn function_ids(abi: &Value) -> TonResult<Vec<FunctionIds>> {
+    let version = abi_version(abi)?;
+    abi_items(abi, "functions")
+        .map(|function| {
+            let (input_id, output_id) = function_id_pair(function, version)?;
+            Ok(FunctionIds { name: function_name(function)?.to_owned(), input_id, output_id })
+        })
+        .collect()
+}
+
+pub(crate) fn event_ids(abi: &Value) -> TonResult<Vec<EventId>> {
+    let version = abi_version(abi)?;
+    abi_items(abi, "events")
+        .map(|event| Ok(EventId { name: function_name(event)?.to_owned(), id: event_id(event, version)? }))
+        .collect()
+}
+
+/// Read function id from message body. External inbound message body starts with signature and
+/// header parameters which are skipped
+pub(crate) fn read_function_id(body: &Cell, external_inbound: bool, version: u8, header: &[String]) -> TonResult<u32> {
+    let mut slice = body.slice();
+    if external_inbound {
+        skip_signature_and_header(&mut slice, version, header)?;
+    }
+    slice.get_u32()
+}
+
+fn skip_signature_and_header(slice: &mut CellSlice, version: u8, header: &[String]) -> TonResult<()> {
+    // ABI v1 keeps signature in reference, so only v2 signature is stored in body bits
+    if version >= 2 && slice.get_bit()? {
+        slice.get_raw(512)?;
+    }
+    for param in header {
+        match param.as_str() {
+            "pubkey" => if slice.get_bit()? {
+                slice.get_raw(256)?;
+            },
+            "time" => { slice.get_u64()?; }
+            "expire" => { slice.get_u32()?; }
+            _ => return Err(TonErrorKind::InvalidData(format!("Unsupported ABI header param `{}`", param)).into())
+        }
+    }
+    Ok(())
+}
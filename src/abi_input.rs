@@ -0,0 +1,278 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{AbiFunction, AbiParam, Cell, TonAddress};
+use crate::error::*;
+use serde_json::{Map, Value};
+
+impl AbiFunction {
+    /// Check function input against ABI and convert it to the form accepted by core.
+    ///
+    /// Integers can be passed as JSON numbers or as decimal or `0x` prefixed hex strings and are
+    /// checked to fit the parameter type. Addresses are converted to raw `workchain:hex` form,
+    /// `bytes` can be passed as hex string or array of bytes, tuples as objects or arrays of
+    /// components in ABI order. Error contains path of the invalid parameter,
+    /// e.g. `custodians[1].pubkey`
+    pub fn coerce_input(&self, input: &Value) -> TonResult<Value> {
+        if input.is_null() && self.inputs.is_empty() {
+            return Ok(Value::Object(Map::new()));
+        }
+        coerce_params(&self.inputs, input, "")
+    }
+}
+
+fn invalid(path: &str, msg: impl Into<String>) -> TonError {
+    TonErrorKind::InvalidInputParam(path.to_owned(), msg.into()).into()
+}
+
+fn child_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn coerce_params(params: &[AbiParam], value: &Value, path: &str) -> TonResult<Value> {
+    let object = value.as_object()
+        .ok_or_else(|| invalid(path, format!("expected object, got {}", value)))?;
+
+    if let Some(unknown) = object.keys().find(|key| !params.iter().any(|param| &param.name == *key)) {
+        return Err(invalid(&child_path(path, unknown), "unknown parameter"));
+    }
+
+    let mut result = Map::new();
+    for param in params {
+        let param_path = child_path(path, &param.name);
+        let value = object.get(&param.name)
+            .ok_or_else(|| invalid(&param_path, "missing parameter"))?;
+        result.insert(param.name.clone(), coerce_value(&param.param_type, &param.components, value, &param_path)?);
+    }
+    Ok(Value::Object(result))
+}
+
+fn coerce_value(param_type: &str, components: &[AbiParam], value: &Value, path: &str) -> TonResult<Value> {
+    if param_type.ends_with(']') {
+        return coerce_array(param_type, components, value, path);
+    }
+    if let Some(types) = param_type.strip_prefix("map(").and_then(|types| types.strip_suffix(')')) {
+        return coerce_map(types, components, value, path);
+    }
+
+    match param_type {
+        "tuple" => coerce_tuple(components, value, path),
+        "bool" => match value {
+            Value::Bool(_) => Ok(value.clone()),
+            _ => Err(invalid(path, format!("expected bool, got {}", value)))
+        },
+        "address" => {
+            let address = value.as_str()
+                .ok_or_else(|| invalid(path, format!("expected address string, got {}", value)))?;
+            TonAddress::from_str(address)
+                .map(|address| Value::String(address.to_string()))
+                .map_err(|_| invalid(path, format!("invalid address `{}`", address)))
+        }
+        "bytes" => coerce_bytes(value, None, path),
+        "cell" => {
+            let boc = value.as_str()
+                .ok_or_else(|| invalid(path, format!("expected base64 encoded cell, got {}", value)))?;
+            // empty string is accepted by core as empty cell
+            if !boc.is_empty() {
+                base64::decode(boc).ok()
+                    .and_then(|boc| Cell::from_boc(&boc).ok())
+                    .ok_or_else(|| invalid(path, "invalid base64 encoded cell"))?;
+            }
+            Ok(value.clone())
+        }
+        // VarUInteger 16 holds up to 15 bytes
+        "gram" => coerce_integer(value, 120, false, path),
+        _ => {
+            if let Some(size) = param_type.strip_prefix("fixedbytes") {
+                return coerce_bytes(value, Some(parse_size(size, param_type)?), path);
+            }
+            if let Some(bits) = param_type.strip_prefix("uint") {
+                return coerce_integer(value, parse_size(bits, param_type)?, false, path);
+            }
+            if let Some(bits) = param_type.strip_prefix("int") {
+                return coerce_integer(value, parse_size(bits, param_type)?, true, path);
+            }
+            // types unknown to client are checked by core
+            Ok(value.clone())
+        }
+    }
+}
+
+fn parse_size(size: &str, param_type: &str) -> TonResult<usize> {
+    size.parse()
+        .map_err(|_| TonErrorKind::InvalidData(format!("Invalid ABI param type `{}`", param_type)).into())
+}
+
+fn coerce_array(param_type: &str, components: &[AbiParam], value: &Value, path: &str) -> TonResult<Value> {
+    let open = param_type.rfind('[')
+        .ok_or_else(|| TonErrorKind::InvalidData(format!("Invalid ABI param type `{}`", param_type)))?;
+    let item_type = &param_type[..open];
+    let size = &param_type[open + 1..param_type.len() - 1];
+
+    let items = value.as_array()
+        .ok_or_else(|| invalid(path, format!("expected array, got {}", value)))?;
+    if !size.is_empty() {
+        let size = parse_size(size, param_type)?;
+        if items.len() != size {
+            return Err(invalid(path, format!("expected {} items, got {}", size, items.len())));
+        }
+    }
+
+    items.iter()
+        .enumerate()
+        .map(|(index, item)| coerce_value(item_type, components, item, &format!("{}[{}]", path, index)))
+        .collect::<TonResult<Vec<_>>>()
+        .map(Value::Array)
+}
+
+fn coerce_map(types: &str, components: &[AbiParam], value: &Value, path: &str) -> TonResult<Value> {
+    let (key_type, value_type) = split_map_types(types)
+        .ok_or_else(|| TonErrorKind::InvalidData(format!("Invalid ABI map type `map({})`", types)))?;
+
+    let object = value.as_object()
+        .ok_or_else(|| invalid(path, format!("expected object, got {}", value)))?;
+
+    let mut result = Map::new();
+    for (key, item) in object {
+        let item_path = format!("{}[{}]", path, key);
+        let key = match coerce_value(key_type, &[], &Value::String(key.clone()), &item_path)? {
+            Value::String(key) => key,
+            key => key.to_string()
+        };
+        result.insert(key, coerce_value(value_type, components, item, &item_path)?);
+    }
+    Ok(Value::Object(result))
+}
+
+// split `K,V` of map type on the top level comma
+fn split_map_types(types: &str) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (index, char) in types.char_indices() {
+        match char {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => return Some((&types[..index], &types[index + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn coerce_tuple(components: &[AbiParam], value: &Value, path: &str) -> TonResult<Value> {
+    match value {
+        // Rust tuples are serialized as arrays
+        Value::Array(items) => {
+            if items.len() != components.len() {
+                return Err(invalid(path, format!("expected {} tuple components, got {}", components.len(), items.len())));
+            }
+            let object = components.iter()
+                .zip(items)
+                .map(|(component, item)| (component.name.clone(), item.clone()))
+                .collect();
+            coerce_params(components, &Value::Object(object), path)
+        }
+        _ => coerce_params(components, value, path)
+    }
+}
+
+fn coerce_bytes(value: &Value, size: Option<usize>, path: &str) -> TonResult<Value> {
+    let bytes = match value {
+        Value::String(string) => hex::decode(string)
+            .map_err(|_| invalid(path, format!("invalid hex string `{}`", string)))?,
+        Value::Array(items) => items.iter()
+            .map(|item| item.as_u64()
+                .filter(|byte| *byte <= u8::MAX as u64)
+                .map(|byte| byte as u8))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| invalid(path, "array contains non byte values"))?,
+        _ => return Err(invalid(path, format!("expected hex string or array of bytes, got {}", value)))
+    };
+    if let Some(size) = size {
+        if bytes.len() != size {
+            return Err(invalid(path, format!("expected {} bytes, got {}", size, bytes.len())));
+        }
+    }
+    Ok(Value::String(hex::encode(bytes)))
+}
+
+fn coerce_integer(value: &Value, bits: usize, signed: bool, path: &str) -> TonResult<Value> {
+    let string = match value {
+        Value::Number(number) if number.is_u64() || number.is_i64() => number.to_string(),
+        Value::String(string) => string.clone(),
+        _ => return Err(invalid(path, format!("expected integer, got {}", value)))
+    };
+
+    let (negative, magnitude) = parse_integer(&string)
+        .ok_or_else(|| invalid(path, format!("invalid integer `{}`", string)))?;
+
+    let fits = if !signed {
+        !negative && bit_length(&magnitude) <= bits
+    } else if negative {
+        // lowest value of signed type is -2^(bits - 1)
+        bit_length(&magnitude) < bits || is_power_of_two(&magnitude) && bit_length(&magnitude) == bits
+    } else {
+        bit_length(&magnitude) < bits
+    };
+    if !fits {
+        let kind = if signed { "int" } else { "uint" };
+        return Err(invalid(path, format!("value {} does not fit into {}{}", string, kind, bits)));
+    }
+
+    Ok(Value::String(string))
+}
+
+/// Sign and big-endian magnitude of decimal or `0x` prefixed hex integer
+pub(crate) fn parse_integer(string: &str) -> Option<(bool, Vec<u8>)> {
+    let (negative, digits) = match string.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, string)
+    };
+
+    let magnitude = match digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        Some(hex) => {
+            if hex.is_empty() {
+                return None;
+            }
+            let hex = if hex.len() % 2 == 1 { format!("0{}", hex) } else { hex.to_owned() };
+            hex::decode(hex).ok()?
+        }
+        None => decimal_magnitude(digits)?
+    };
+    Some((negative, magnitude))
+}
+
+fn decimal_magnitude(digits: &str) -> Option<Vec<u8>> {
+    if digits.is_empty() {
+        return None;
+    }
+    let mut bytes: Vec<u8> = vec![];
+    for char in digits.chars() {
+        let mut carry = char.to_digit(10)?;
+        for byte in bytes.iter_mut().rev() {
+            let product = *byte as u32 * 10 + carry;
+            *byte = product as u8;
+            carry = product >> 8;
+        }
+        while carry > 0 {
+            bytes.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    Some(bytes)
+}
+
+pub(crate) fn bit_length(magnitude: &[u8]) -> usize {
+    match magnitude.iter().position(|byte| *byte != 0) {
+        Some(first) => (magnitude.len() - first) * 8 - magnitude[first].leading_zeros() as usize,
+        None => 0
+    }
+}
+
+fn is_power_of_two(magnitude: &[u8]) -> bool {
+    magnitude.iter().map(|byte| byte.count_ones()).sum::<u32>() == 1
+}
@@ -0,0 +1,281 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{
+    parse_message, Cell, DecodedMessageBody, JsonValue, MessageBodyType, MessageType, ParsedMessage,
+    TonContracts,
+};
+use crate::abi::{abi_header, abi_version, event_ids, function_ids, read_function_id};
+use crate::error::*;
+use serde_json::Value;
+use std::collections::HashMap;
+
+struct RegisteredAbi {
+    name: String,
+    abi: Value,
+    version: u8,
+    header: Vec<String>,
+}
+
+/// Message body decoded by `AbiRegistry` with name of the matched ABI
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedWithAbi {
+    pub abi_name: String,
+    pub body: DecodedMessageBody,
+}
+
+/// Result of `AbiRegistry::decode_message`
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegistryDecodedMessage {
+    pub message: ParsedMessage,
+    /// `None` if message has no body
+    pub body: Option<DecodedWithAbi>,
+}
+
+/// Index of function and event ids of many ABIs used to decode message bodies without knowing
+/// contract type. ABIs can be also bound to contract code hash to resolve id collisions
+#[derive(Default)]
+pub struct AbiRegistry {
+    abis: Vec<RegisteredAbi>,
+    input_ids: HashMap<u32, Vec<(usize, String)>>,
+    output_ids: HashMap<u32, Vec<(usize, String, MessageBodyType)>>,
+    code_hashes: HashMap<String, usize>,
+}
+
+impl AbiRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register ABI under given name. ABI registered with existing name replaces the old one
+    pub fn register(&mut self, name: &str, abi: JsonValue) -> TonResult<()> {
+        let abi = abi.to_value()?;
+        let registered = RegisteredAbi {
+            name: name.to_owned(),
+            version: abi_version(&abi)?,
+            header: abi_header(&abi)?,
+            abi,
+        };
+        let functions = function_ids(&registered.abi)?;
+        let events = event_ids(&registered.abi)?;
+
+        let index = match self.abis.iter().position(|abi| abi.name == name) {
+            Some(index) => {
+                self.abis[index] = registered;
+                self.remove_ids(index);
+                index
+            }
+            None => {
+                self.abis.push(registered);
+                self.abis.len() - 1
+            }
+        };
+
+        for function in functions {
+            self.input_ids.entry(function.input_id).or_default().push((index, function.name.clone()));
+            self.output_ids.entry(function.output_id).or_default()
+                .push((index, function.name, MessageBodyType::Output));
+        }
+        for event in events {
+            self.output_ids.entry(event.id).or_default().push((index, event.name, MessageBodyType::Event));
+        }
+        Ok(())
+    }
+
+    /// Bind registered ABI to contract code hash (hex encoded)
+    pub fn register_code_hash(&mut self, name: &str, code_hash: &str) -> TonResult<()> {
+        let index = self.index_of(name)
+            .ok_or_else(|| TonErrorKind::InvalidArg(format!("ABI `{}` is not registered", name)))?;
+        self.code_hashes.insert(code_hash.to_lowercase(), index);
+        Ok(())
+    }
+
+    /// Register ABI and bind it to hash of the code from contract image. Returns code hash
+    pub fn register_image(
+        &mut self,
+        contracts: &TonContracts,
+        name: &str,
+        abi: JsonValue,
+        image: &[u8],
+    ) -> TonResult<String> {
+        let code_hash = contracts.get_boc_hash(&image_code(image)?.to_boc()?)?;
+        self.register(name, abi)?;
+        self.register_code_hash(name, &code_hash)?;
+        Ok(code_hash)
+    }
+
+    pub fn abi(&self, name: &str) -> Option<&Value> {
+        self.index_of(name).map(|index| &self.abis[index].abi)
+    }
+
+    /// Name and ABI bound to code hash
+    pub fn abi_by_code_hash(&self, code_hash: &str) -> Option<(&str, &Value)> {
+        self.code_hashes.get(&code_hash.to_lowercase())
+            .map(|index| (self.abis[*index].name.as_str(), &self.abis[*index].abi))
+    }
+
+    /// Parse message and decode its body with matching registered ABI. If `code_hash` of
+    /// the contract is known only ABI bound to it is used
+    pub fn decode_message(
+        &self,
+        contracts: &TonContracts,
+        message_boc: &[u8],
+        code_hash: Option<&str>,
+    ) -> TonResult<RegistryDecodedMessage> {
+        let message = parse_message(message_boc)?;
+        let body = match &message.body {
+            Some(body) => Some(self.decode_body_cell(contracts, body, message.message_type, code_hash)?),
+            None => None
+        };
+        Ok(RegistryDecodedMessage { message, body })
+    }
+
+    /// Decode message body BOC with matching registered ABI
+    pub fn decode_body(
+        &self,
+        contracts: &TonContracts,
+        body: &[u8],
+        message_type: MessageType,
+        code_hash: Option<&str>,
+    ) -> TonResult<DecodedWithAbi> {
+        self.decode_body_cell(contracts, &Cell::from_boc(body)?, message_type, code_hash)
+    }
+
+    fn decode_body_cell(
+        &self,
+        contracts: &TonContracts,
+        body: &Cell,
+        message_type: MessageType,
+        code_hash: Option<&str>,
+    ) -> TonResult<DecodedWithAbi> {
+        let candidates = self.candidates(body, message_type, code_hash)?;
+
+        let body_boc = body.to_boc()?;
+        let mut last_error = None;
+        for (index, name, body_type) in candidates {
+            let abi: JsonValue = self.abis[index].abi.clone().into();
+            let decoded = match message_type {
+                MessageType::Internal if body_type == MessageBodyType::Output =>
+                    contracts.decode_output_message_body(abi, &body_boc),
+                MessageType::Internal => contracts.decode_input_message_body(abi, &body_boc, true),
+                MessageType::ExternalIn => contracts.decode_input_message_body(abi, &body_boc, false),
+                MessageType::ExternalOut => contracts.decode_output_message_body(abi, &body_boc),
+            };
+            match decoded {
+                Ok(decoded) if decoded.function == name => return Ok(DecodedWithAbi {
+                    abi_name: self.abis[index].name.clone(),
+                    body: DecodedMessageBody {
+                        body_type,
+                        name: decoded.function,
+                        params: decoded.output,
+                    },
+                }),
+                Ok(_) => {}
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(||
+            TonErrorKind::InvalidData("No registered ABI matches message body".to_owned()).into()))
+    }
+
+    /// ABI names, function or event names and body types which ids match message body.
+    /// Body is not decoded, so all candidates are returned in case of id collision
+    pub fn matching(
+        &self,
+        body: &Cell,
+        message_type: MessageType,
+        code_hash: Option<&str>,
+    ) -> TonResult<Vec<(String, String, MessageBodyType)>> {
+        Ok(self.candidates(body, message_type, code_hash)?
+            .into_iter()
+            .map(|(index, name, body_type)| (self.abis[index].name.clone(), name, body_type))
+            .collect())
+    }
+
+    fn candidates(
+        &self,
+        body: &Cell,
+        message_type: MessageType,
+        code_hash: Option<&str>,
+    ) -> TonResult<Vec<(usize, String, MessageBodyType)>> {
+        let only_abi = match code_hash {
+            Some(hash) => Some(*self.code_hashes.get(&hash.to_lowercase())
+                .ok_or_else(|| TonErrorKind::InvalidArg(format!("No ABI registered for code hash {}", hash)))?),
+            None => None
+        };
+        let allowed = |index: usize| only_abi.map(|only| only == index).unwrap_or(true);
+
+        let candidates: Vec<(usize, String, MessageBodyType)> = match message_type {
+            MessageType::Internal => {
+                let id = read_function_id(body, false, 0, &[])?;
+                // internal function responses have output id with the highest bit set
+                if id & 0x8000_0000 != 0 {
+                    self.output_ids.get(&id)
+                        .map(|items| items.iter()
+                            .filter(|(_, _, body_type)| *body_type == MessageBodyType::Output)
+                            .cloned()
+                            .collect())
+                        .unwrap_or_default()
+                } else {
+                    self.input_candidates(id)
+                }
+            }
+            MessageType::ExternalIn => {
+                // function id position depends on ABI header, so it is read for every ABI
+                let mut candidates = vec![];
+                for (index, abi) in self.abis.iter().enumerate().filter(|(index, _)| allowed(*index)) {
+                    if let Ok(id) = read_function_id(body, true, abi.version, &abi.header) {
+                        candidates.extend(self.input_candidates(id)
+                            .into_iter()
+                            .filter(|(candidate, _, _)| *candidate == index));
+                    }
+                }
+                candidates
+            }
+            MessageType::ExternalOut => {
+                let id = read_function_id(body, false, 0, &[])?;
+                self.output_ids.get(&id).cloned().unwrap_or_default()
+            }
+        };
+
+        Ok(candidates.into_iter().filter(|(index, _, _)| allowed(*index)).collect())
+    }
+
+    fn input_candidates(&self, id: u32) -> Vec<(usize, String, MessageBodyType)> {
+        self.input_ids.get(&id)
+            .map(|functions| functions.iter()
+                .map(|(index, name)| (*index, name.clone(), MessageBodyType::Input))
+                .collect())
+            .unwrap_or_default()
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.abis.iter().position(|abi| abi.name == name)
+    }
+
+    fn remove_ids(&mut self, index: usize) {
+        for functions in self.input_ids.values_mut() {
+            functions.retain(|(abi, _)| *abi != index);
+        }
+        for items in self.output_ids.values_mut() {
+            items.retain(|(abi, _, _)| *abi != index);
+        }
+    }
+}
+
+/// Code cell of contract image (`StateInit`)
+fn image_code(image: &[u8]) -> TonResult<Cell> {
+    let mut slice = Cell::from_boc(image)?.slice();
+    if slice.get_bit()? {       // split_depth
+        slice.get_bits(5)?;
+    }
+    if slice.get_bit()? {       // special
+        slice.get_bits(2)?;
+    }
+    if !slice.get_bit()? {
+        return Err(TonErrorKind::InvalidData("Contract image has no code".to_owned()).into());
+    }
+    slice.get_reference()
+}
@@ -0,0 +1,173 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+//! Serde helpers mapping ABI function outputs to Rust types. Use them with `#[serde(with = "...")]`
+//! attribute on fields of structs passed to `ResultOfRun::output_as`.
+//!
+//! Core returns integers as decimal or `0x` prefixed hex strings, `bytes` as hex strings and
+//! `cell` as base64 encoded BOC. Tuples are returned as objects and can be mapped to structs
+//! directly. Addresses, token amounts and 256-bit integers are deserialized into `TonAddress`,
+//! `Grams`, `U256` and `I256` without helpers.
+
+use crate::Cell;
+use crate::abi_input::{bit_length, parse_integer};
+use serde::de::{Error, Visitor};
+use serde::{Deserializer, Serializer};
+
+// sign and magnitude of integer not exceeding 128 bits
+struct IntegerVisitor;
+
+impl<'de> Visitor<'de> for IntegerVisitor {
+    type Value = (bool, u128);
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("integer as number, decimal or 0x prefixed hex string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> where E: Error {
+        let (negative, magnitude) = parse_integer(v)
+            .ok_or_else(|| E::custom(format!("invalid integer `{}`", v)))?;
+        if bit_length(&magnitude) > 128 {
+            return Err(E::custom(format!("integer {} exceeds 128 bits", v)));
+        }
+        let magnitude = magnitude.iter().fold(0u128, |value, byte| value << 8 | *byte as u128);
+        Ok((negative, magnitude))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> where E: Error {
+        Ok((false, v as u128))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> where E: Error {
+        Ok((v < 0, v.wrapping_abs() as u64 as u128))
+    }
+}
+
+/// Unsigned ABI integer (`uintN`, `gram`) into `u8`..`u128`
+pub mod uint {
+    use super::*;
+    use std::convert::TryFrom;
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<u128>,
+    {
+        let (negative, magnitude) = deserializer.deserialize_any(IntegerVisitor)?;
+        if negative {
+            return Err(D::Error::custom(format!("negative value -{} for unsigned integer", magnitude)));
+        }
+        T::try_from(magnitude)
+            .map_err(|_| D::Error::custom(format!("integer {} is out of range", magnitude)))
+    }
+
+    /// Serialized as decimal string accepted as ABI function input
+    pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: std::fmt::Display,
+    {
+        serializer.serialize_str(&value.to_string())
+    }
+}
+
+/// Signed ABI integer (`intN`) into `i8`..`i128`
+pub mod int {
+    use super::*;
+    use std::convert::TryFrom;
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<i128>,
+    {
+        let (negative, magnitude) = deserializer.deserialize_any(IntegerVisitor)?;
+        let value = if negative && magnitude == 1 << 127 {
+            Some(i128::MIN)
+        } else {
+            i128::try_from(magnitude).ok().map(|value| if negative { -value } else { value })
+        };
+        value.and_then(|value| T::try_from(value).ok())
+            .ok_or_else(|| D::Error::custom(format!(
+                "integer {}{} is out of range", if negative { "-" } else { "" }, magnitude)))
+    }
+
+    /// Serialized as decimal string accepted as ABI function input
+    pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: std::fmt::Display,
+    {
+        serializer.serialize_str(&value.to_string())
+    }
+}
+
+/// ABI `bytes` and `fixedbytesN` hex string into `Vec<u8>`
+pub mod bytes {
+    use super::*;
+
+    struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("hex string")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> where E: Error {
+            hex::decode(v).map_err(|err| E::custom(format!("error decode bytes: {}", err)))
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(BytesVisitor)
+    }
+
+    pub fn serialize<S>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&hex::encode(value))
+    }
+}
+
+/// ABI `cell` base64 encoded BOC into `Cell`
+pub mod cell {
+    use super::*;
+
+    struct CellVisitor;
+
+    impl<'de> Visitor<'de> for CellVisitor {
+        type Value = Cell;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("base64 encoded bag of cells")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> where E: Error {
+            base64::decode(v).ok()
+                .and_then(|boc| Cell::from_boc(&boc).ok())
+                .ok_or_else(|| E::custom(format!("error decode cell: {}", v)))
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Cell, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(CellVisitor)
+    }
+
+    pub fn serialize<S>(value: &Cell, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let boc = value.to_boc().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&base64::encode(&boc))
+    }
+}
@@ -0,0 +1,373 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+//! TVM cells and bag of cells (BOC) used to build messages and read get-method values on the
+//! client side.
+//!
+//! Cells are thin wrappers around `ton_types` ones, so BOCs and hashes are exactly the same as
+//! core produces. Wrappers keep crate errors and add helpers for client-side types (`Grams`,
+//! `TonAddress`)
+
+use crate::{Grams, TonAddress};
+use crate::error::*;
+use std::convert::TryFrom;
+
+/// Maximum number of data bits in a cell
+pub const MAX_DATA_BITS: usize = 1023;
+/// Maximum number of references in a cell
+pub const MAX_REFERENCES: usize = 4;
+
+fn invalid_data(err: impl std::fmt::Display) -> TonError {
+    TonErrorKind::InvalidData(err.to_string()).into()
+}
+
+fn invalid_operation(err: impl std::fmt::Display) -> TonError {
+    TonErrorKind::InvalidOperation(err.to_string()).into()
+}
+
+/// TVM cell: up to 1023 data bits and up to 4 references to other cells. Cloning is cheap
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Cell(ton_types::Cell);
+
+impl Cell {
+    /// Data bits of the cell. Bits after `bit_len` in the last byte are zero
+    pub fn data(&self) -> Vec<u8> {
+        let mut slice = ton_types::SliceData::from(self.0.clone());
+        // full cell is always readable
+        slice.get_next_bits(self.bit_len()).unwrap_or_default()
+    }
+
+    pub fn bit_len(&self) -> usize {
+        self.0.bit_length()
+    }
+
+    pub fn references(&self) -> Vec<Cell> {
+        (0..self.0.references_count())
+            .filter_map(|index| self.0.reference(index).ok().map(Cell))
+            .collect()
+    }
+
+    pub fn reference(&self, index: usize) -> TonResult<Cell> {
+        self.0.reference(index)
+            .map(Cell)
+            .map_err(|_| TonErrorKind::InvalidData(format!("Cell has no reference {}", index)).into())
+    }
+
+    pub fn is_exotic(&self) -> bool {
+        self.0.cell_type() != ton_types::CellType::Ordinary
+    }
+
+    pub fn level_mask(&self) -> u8 {
+        self.0.level_mask().mask()
+    }
+
+    /// Reader positioned at the beginning of the cell
+    pub fn slice(&self) -> CellSlice {
+        CellSlice(ton_types::SliceData::from(self.0.clone()))
+    }
+
+    /// Deserialize single root bag of cells
+    pub fn from_boc(boc: &[u8]) -> TonResult<Cell> {
+        deserialize_boc(boc)
+    }
+
+    /// Serialize cell tree into bag of cells
+    pub fn to_boc(&self) -> TonResult<Vec<u8>> {
+        serialize_boc(self)
+    }
+}
+
+impl From<ton_types::Cell> for Cell {
+    fn from(cell: ton_types::Cell) -> Self {
+        Cell(cell)
+    }
+}
+
+impl From<Cell> for ton_types::Cell {
+    fn from(cell: Cell) -> Self {
+        cell.0
+    }
+}
+
+/// Builder for constructing new cells
+#[derive(Clone, Default, Debug)]
+pub struct CellBuilder(ton_types::BuilderData);
+
+impl CellBuilder {
+    pub fn new() -> CellBuilder {
+        CellBuilder::default()
+    }
+
+    pub fn bit_len(&self) -> usize {
+        self.0.length_in_bits()
+    }
+
+    pub fn remaining_bits(&self) -> usize {
+        MAX_DATA_BITS - self.bit_len()
+    }
+
+    pub fn remaining_references(&self) -> usize {
+        MAX_REFERENCES - self.0.references_used()
+    }
+
+    pub fn append_bit(&mut self, bit: bool) -> TonResult<&mut Self> {
+        self.append_raw(&[if bit { 0x80 } else { 0 }], 1)
+    }
+
+    /// Append `bits` lowest bits of `value` starting from the most significant one
+    pub fn append_bits(&mut self, value: u128, bits: usize) -> TonResult<&mut Self> {
+        if bits > 128 {
+            return Err(TonErrorKind::InvalidArg(format!("Can not append {} bits from integer", bits)).into());
+        }
+        if bits == 0 {
+            return Ok(self);
+        }
+        let data = (value << (128 - bits)).to_be_bytes();
+        self.append_raw(&data, bits)
+    }
+
+    pub fn append_u8(&mut self, value: u8) -> TonResult<&mut Self> {
+        self.append_bits(value as u128, 8)
+    }
+
+    pub fn append_u32(&mut self, value: u32) -> TonResult<&mut Self> {
+        self.append_bits(value as u128, 32)
+    }
+
+    pub fn append_u64(&mut self, value: u64) -> TonResult<&mut Self> {
+        self.append_bits(value as u128, 64)
+    }
+
+    pub fn append_bytes(&mut self, bytes: &[u8]) -> TonResult<&mut Self> {
+        self.append_raw(bytes, bytes.len() * 8)
+    }
+
+    /// Append first `bits` bits of `data`
+    pub fn append_raw(&mut self, data: &[u8], bits: usize) -> TonResult<&mut Self> {
+        if bits > data.len() * 8 {
+            return Err(TonErrorKind::InvalidArg(format!("Not enough data for {} bits", bits)).into());
+        }
+        self.check_bits(bits)?;
+        self.0.append_raw(data, bits).map_err(invalid_operation)?;
+        Ok(self)
+    }
+
+    pub fn append_reference(&mut self, cell: Cell) -> TonResult<&mut Self> {
+        if self.remaining_references() == 0 {
+            return Err(TonErrorKind::InvalidOperation("Cell references overflow".to_owned()).into());
+        }
+        self.0.checked_append_reference(cell.0).map_err(invalid_operation)?;
+        Ok(self)
+    }
+
+    /// Append remaining bits and references of `slice`
+    pub fn append_slice(&mut self, slice: &CellSlice) -> TonResult<&mut Self> {
+        let mut slice = slice.clone();
+        let bits = slice.remaining_bits();
+        let data = slice.get_raw(bits)?;
+        self.append_raw(&data, bits)?;
+        while slice.remaining_references() > 0 {
+            self.append_reference(slice.get_reference()?)?;
+        }
+        Ok(self)
+    }
+
+    /// Append `Grams` amount in `VarUInteger 16` format
+    pub fn append_grams(&mut self, grams: Grams) -> TonResult<&mut Self> {
+        let bytes = (128 - grams.0.leading_zeros() as usize + 7) / 8;
+        if bytes > 15 {
+            return Err(TonErrorKind::InvalidArg(format!("Grams amount is too big: {}", grams)).into());
+        }
+        self.append_bits(bytes as u128, 4)?;
+        self.append_bits(grams.0, bytes * 8)
+    }
+
+    /// Append `MsgAddressInt` or `addr_none` if `address` is `None`
+    pub fn append_address(&mut self, address: Option<&TonAddress>) -> TonResult<&mut Self> {
+        fn append_anycast(builder: &mut CellBuilder, depth: u8, prefix: u32) -> TonResult<()> {
+            if depth == 0 || depth > 30 {
+                return Err(TonErrorKind::InvalidArg(format!("Invalid anycast depth {}", depth)).into());
+            }
+            builder.append_bit(true)?;
+            builder.append_bits(depth as u128, 5)?;
+            builder.append_bits(prefix as u128, depth as usize)?;
+            Ok(())
+        }
+
+        match address {
+            None => { self.append_bits(0b00, 2)?; }
+            Some(TonAddress::Std(workchain, address)) => {
+                self.append_bits(0b100, 3)?;
+                self.append_u8(*workchain as u8)?;
+                self.append_bytes(address)?;
+            }
+            Some(TonAddress::AnycastStd(depth, prefix, workchain, address)) => {
+                self.append_bits(0b10, 2)?;
+                append_anycast(self, *depth, *prefix)?;
+                self.append_u8(*workchain as u8)?;
+                self.append_bytes(address)?;
+            }
+            Some(TonAddress::Var(workchain, address)) => {
+                self.append_bits(0b110, 3)?;
+                self.append_bits((address.len() * 8) as u128, 9)?;
+                self.append_u32(*workchain as u32)?;
+                self.append_bytes(address)?;
+            }
+            Some(TonAddress::AnycastVar(depth, prefix, workchain, address)) => {
+                self.append_bits(0b11, 2)?;
+                append_anycast(self, *depth, *prefix)?;
+                self.append_bits((address.len() * 8) as u128, 9)?;
+                self.append_u32(*workchain as u32)?;
+                self.append_bytes(address)?;
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn build(&self) -> TonResult<Cell> {
+        self.0.clone().into_cell().map(Cell).map_err(invalid_operation)
+    }
+
+    fn check_bits(&self, bits: usize) -> TonResult<()> {
+        if bits > self.remaining_bits() {
+            return Err(TonErrorKind::InvalidOperation("Cell data overflow".to_owned()).into());
+        }
+        Ok(())
+    }
+}
+
+/// Reader of cell data and references
+#[derive(Clone, Debug)]
+pub struct CellSlice(ton_types::SliceData);
+
+impl CellSlice {
+    pub fn remaining_bits(&self) -> usize {
+        self.0.remaining_bits()
+    }
+
+    pub fn remaining_references(&self) -> usize {
+        self.0.remaining_references()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining_bits() == 0 && self.remaining_references() == 0
+    }
+
+    pub fn get_bit(&mut self) -> TonResult<bool> {
+        self.check_bits(1)?;
+        self.0.get_next_bit().map_err(invalid_data)
+    }
+
+    /// Read `bits` bits as unsigned integer
+    pub fn get_bits(&mut self, bits: usize) -> TonResult<u128> {
+        if bits > 128 {
+            return Err(TonErrorKind::InvalidArg(format!("Can not read {} bits as integer", bits)).into());
+        }
+        let data = self.get_raw(bits)?;
+        let value = data.iter().fold(0u128, |value, byte| value << 8 | *byte as u128);
+        Ok(value >> (data.len() * 8 - bits))
+    }
+
+    pub fn get_u8(&mut self) -> TonResult<u8> {
+        Ok(self.get_bits(8)? as u8)
+    }
+
+    pub fn get_u32(&mut self) -> TonResult<u32> {
+        Ok(self.get_bits(32)? as u32)
+    }
+
+    pub fn get_u64(&mut self) -> TonResult<u64> {
+        Ok(self.get_bits(64)? as u64)
+    }
+
+    pub fn get_bytes(&mut self, count: usize) -> TonResult<Vec<u8>> {
+        self.get_raw(count * 8)
+    }
+
+    /// Read `bits` bits into bytes. Unused bits of the last byte are zero
+    pub fn get_raw(&mut self, bits: usize) -> TonResult<Vec<u8>> {
+        self.check_bits(bits)?;
+        if bits == 0 {
+            return Ok(vec![]);
+        }
+        self.0.get_next_bits(bits).map_err(invalid_data)
+    }
+
+    pub fn get_reference(&mut self) -> TonResult<Cell> {
+        if self.remaining_references() == 0 {
+            return Err(TonErrorKind::InvalidData("Cell references underflow".to_owned()).into());
+        }
+        self.0.checked_drain_reference().map(Cell).map_err(invalid_data)
+    }
+
+    /// Read `Grams` amount stored in `VarUInteger 16` format
+    pub fn get_grams(&mut self) -> TonResult<Grams> {
+        let bytes = self.get_bits(4)? as usize;
+        Ok(Grams(self.get_bits(bytes * 8)?))
+    }
+
+    /// Read `MsgAddress`. Returns `None` for `addr_none` and external addresses
+    pub fn get_address(&mut self) -> TonResult<Option<TonAddress>> {
+        let tag = self.get_bits(2)?;
+        match tag {
+            0b00 => Ok(None),
+            0b01 => {
+                let len = self.get_bits(9)? as usize;
+                self.get_raw(len)?;
+                Ok(None)
+            }
+            _ => {
+                let anycast = if self.get_bit()? {
+                    let depth = self.get_bits(5)? as u8;
+                    Some((depth, self.get_bits(depth as usize)? as u32))
+                } else {
+                    None
+                };
+                let address = if tag == 0b10 {
+                    let workchain = self.get_u8()? as i8;
+                    let address = <[u8; 32]>::try_from(&self.get_bytes(32)?[..])?;
+                    match anycast {
+                        Some((depth, prefix)) => TonAddress::AnycastStd(depth, prefix, workchain, address),
+                        None => TonAddress::Std(workchain, address)
+                    }
+                } else {
+                    let len = self.get_bits(9)? as usize;
+                    let workchain = self.get_u32()? as i32;
+                    let address = self.get_raw(len)?;
+                    match anycast {
+                        Some((depth, prefix)) => TonAddress::AnycastVar(depth, prefix, workchain, address),
+                        None => TonAddress::Var(workchain, address)
+                    }
+                };
+                Ok(Some(address))
+            }
+        }
+    }
+
+    /// Build new cell from remaining bits and references
+    pub fn into_cell(self) -> TonResult<Cell> {
+        let mut builder = CellBuilder::new();
+        builder.append_slice(&self)?;
+        builder.build()
+    }
+
+    fn check_bits(&self, bits: usize) -> TonResult<()> {
+        if bits > self.remaining_bits() {
+            return Err(TonErrorKind::InvalidData("Cell data underflow".to_owned()).into());
+        }
+        Ok(())
+    }
+}
+
+/// Deserialize bag of cells with a single root
+pub fn deserialize_boc(boc: &[u8]) -> TonResult<Cell> {
+    ton_types::cells_serialization::deserialize_tree_of_cells(&mut std::io::Cursor::new(boc))
+        .map(Cell)
+        .map_err(invalid_data)
+}
+
+/// Serialize cell tree into bag of cells with CRC32C. Equal subtrees are stored once
+pub fn serialize_boc(root: &Cell) -> TonResult<Vec<u8>> {
+    ton_types::cells_serialization::serialize_toc(&root.0).map_err(invalid_operation)
+}
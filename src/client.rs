@@ -3,7 +3,9 @@
  */
 
 use crate::interop::Interop;
-use crate::{TonCrypto, TonContracts, TonQueries, TonResult};
+use crate::{DeployParams, Ed25519KeyPair, Giver, Grams, ResultOfDeploy, TonAddress};
+use crate::{TonCrypto, TonContracts, TonQueries};
+use crate::error::*;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 
@@ -87,7 +89,53 @@ impl TonClient {
 
     /// Set parameters for node interaction
     pub fn setup(&self, config: &TonClientConfig) -> TonResult<()> {
-        Interop::json_request(self.context, "setup", config)
+        Interop::json_request::<_, ()>(self.context, "setup", config)?;
+        if let Some(count) = config.message_retries_count {
+            self.contracts.set_message_retries_count(count);
+        }
+        Ok(())
+    }
+
+    /// Send tokens from `giver` to `address` and wait until they are received
+    pub fn fund_account(&self, giver: &dyn Giver, address: &TonAddress, value: Grams) -> TonResult<()> {
+        let result = giver.give(self, address, value)?;
+        // wait for transaction of `address` processing message sent by giver, so tokens are
+        // received even if account already had positive balance
+        let messages = result.transaction["out_msgs"].as_array()
+            .filter(|messages| !messages.is_empty())
+            .cloned()
+            .ok_or_else(|| TonErrorKind::InvalidData("Giver transaction has no output messages".to_owned()))?;
+        self.queries.transactions.wait_for(
+            serde_json::json!({
+                "account_addr": { "eq": address.to_string() },
+                "in_msg": { "in": messages }
+            }).into(),
+            "id")?;
+        Ok(())
+    }
+
+    /// Calculate contract address, fund it with `value` tokens from `giver` and deploy contract.
+    /// Funding is skipped if contract is already deployed
+    pub fn deploy_funded(
+        &self,
+        giver: &dyn Giver,
+        value: Grams,
+        params: &DeployParams,
+        keys: &Ed25519KeyPair,
+    ) -> TonResult<ResultOfDeploy> {
+        let address = self.contracts.get_deploy_address(
+            params.abi.clone(), &params.image, params.init_params.clone(), &keys.public, params.workchain_id)?;
+        if !self.contracts.is_account_active(&address)? {
+            self.fund_account(giver, &address, value)?;
+        }
+        self.contracts.deploy(
+            params.abi.clone(),
+            &params.image,
+            params.constructor_header.clone(),
+            params.constructor_params.clone(),
+            params.init_params.clone(),
+            keys,
+            params.workchain_id)
     }
 
     /// Request core 
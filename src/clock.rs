@@ -0,0 +1,128 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::LocalRunContext;
+use crate::error::*;
+
+// blocks start at logical time multiple of this value
+const BLOCK_LT_STEP: u64 = 1_000_000;
+
+const DEFAULT_LT_STEP: u64 = 1_000;
+
+/// Deterministic time source for sequence of local runs.
+///
+/// Every `next_context` call returns context with increased transaction logical time, so
+/// consecutive runs see consistent `now` and `lt` values. Advancing time starts a new block.
+/// Contexts returned by `next_context` are recorded and available with `history`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalClock {
+    time: u32,
+    lt: u64,
+    block_lt: u64,
+    lt_step: u64,
+    config_boc: Option<String>,
+    history: Vec<LocalRunContext>,
+}
+
+impl LocalClock {
+    /// Clock starting at unix `time`
+    pub fn new(time: u32) -> Self {
+        LocalClock {
+            time,
+            lt: BLOCK_LT_STEP,
+            block_lt: BLOCK_LT_STEP,
+            lt_step: DEFAULT_LT_STEP,
+            config_boc: None,
+            history: vec![],
+        }
+    }
+
+    /// Clock starting at current system time
+    pub fn now() -> Self {
+        let time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as u32)
+            .unwrap_or_default();
+        Self::new(time)
+    }
+
+    /// Set starting logical time. Block logical time is rounded down to block boundary
+    pub fn with_lt(mut self, lt: u64) -> Self {
+        self.lt = lt;
+        self.block_lt = lt - lt % BLOCK_LT_STEP;
+        self
+    }
+
+    /// Set logical time increment between runs. Default is 1000
+    pub fn with_lt_step(mut self, lt_step: u64) -> Self {
+        self.lt_step = lt_step.max(1);
+        self
+    }
+
+    /// Set blockchain config passed to every run
+    pub fn with_config_boc(mut self, config_boc: String) -> Self {
+        self.config_boc = Some(config_boc);
+        self
+    }
+
+    pub fn time(&self) -> u32 {
+        self.time
+    }
+
+    /// Logical time of the last run
+    pub fn lt(&self) -> u64 {
+        self.lt
+    }
+
+    pub fn block_lt(&self) -> u64 {
+        self.block_lt
+    }
+
+    /// Move time forward by `seconds` and start a new block
+    pub fn advance(&mut self, seconds: u32) {
+        self.time = self.time.saturating_add(seconds);
+        self.next_block();
+    }
+
+    /// Set time and start a new block. Time can't go backward
+    pub fn set_time(&mut self, time: u32) -> TonResult<()> {
+        if time < self.time {
+            return Err(TonErrorKind::InvalidArg(
+                format!("Clock time can't go backward: {} < {}", time, self.time)).into());
+        }
+        self.time = time;
+        self.next_block();
+        Ok(())
+    }
+
+    fn next_block(&mut self) {
+        self.block_lt = (self.lt / BLOCK_LT_STEP + 1) * BLOCK_LT_STEP;
+        self.lt = self.block_lt;
+    }
+
+    /// Context of current time and logical time which doesn't advance the clock. Use it for
+    /// get-methods and other runs not changing account state
+    pub fn context(&self) -> LocalRunContext {
+        LocalRunContext {
+            config_boc: self.config_boc.clone(),
+            time: Some(self.time),
+            transaction_lt: Some(self.lt),
+            block_lt: Some(self.block_lt),
+        }
+    }
+
+    /// Context for the next transaction. Logical time is incremented and context is recorded
+    /// to history
+    pub fn next_context(&mut self) -> LocalRunContext {
+        self.lt += self.lt_step;
+        let context = self.context();
+        self.history.push(context.clone());
+        context
+    }
+
+    /// Contexts returned by `next_context` in order
+    pub fn history(&self) -> &[LocalRunContext] {
+        &self.history
+    }
+}
@@ -2,11 +2,16 @@
  * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
  */
 
-use crate::{Ed25519KeyPair, Ed25519Public, JsonValue, TonAddress};
+use crate::{AbiInfo, Ed25519KeyPair, Ed25519Public, JsonValue, ProcessingEvent, RetryPolicy, TonAddress};
 use crate::types::option_params_to_value;
 use crate::error::*;
+use crate::retry::{has_error_code, DEFAULT_MESSAGE_RETRIES_COUNT, MESSAGE_EXPIRED_CODE, RetryAttempt};
+use crate::queries::{ParamsOfQuery, ResultOfQuery, TonQueriesCollection};
 use serde_json::Value;
 use crate::interop::{InteropContext, Interop};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 #[derive(Serialize, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -21,6 +26,60 @@ pub(crate) struct ParamsOfDeploy {
     pub try_index: Option<u8>,
 }
 
+/// Contract deploy parameters except keys
+#[derive(Clone, Debug)]
+pub struct DeployParams {
+    pub abi: JsonValue,
+    pub image: Vec<u8>,
+    pub constructor_header: Option<JsonValue>,
+    pub constructor_params: JsonValue,
+    pub init_params: Option<JsonValue>,
+    pub workchain_id: i32,
+}
+
+impl DeployParams {
+    /// Parameters for deploying to workchain 0 without header and initial data
+    pub fn new(abi: JsonValue, image: &[u8], constructor_params: JsonValue) -> Self {
+        DeployParams {
+            abi,
+            image: image.to_vec(),
+            constructor_header: None,
+            constructor_params,
+            init_params: None,
+            workchain_id: 0,
+        }
+    }
+}
+
+/// Contract function call parameters except address and keys
+#[derive(Clone, Debug)]
+pub struct RunParams {
+    pub abi: JsonValue,
+    pub function_name: String,
+    pub header: Option<JsonValue>,
+    pub input: JsonValue,
+}
+
+impl RunParams {
+    /// Parameters for calling function without header
+    pub fn new(abi: JsonValue, function_name: &str, input: JsonValue) -> Self {
+        RunParams { abi, function_name: function_name.to_owned(), header: None, input }
+    }
+}
+
+/// Contract deploy parameters used by `deploy_many`
+#[derive(Clone, Debug)]
+pub struct DeployItem {
+    pub params: DeployParams,
+    pub keys: Ed25519KeyPair,
+}
+
+// `deploy_many` worker report
+enum DeployProgress {
+    Event(usize, ProcessingEvent),
+    Done(usize, TonResult<ResultOfDeploy>),
+}
+
 /// Result of `deploy` function running. Contains address of the contract
 #[derive(Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -63,7 +122,7 @@ pub(crate) struct ParamsOfRun {
     pub try_index: Option<u8>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct LocalRunContext {
     pub config_boc: Option<String>,
@@ -114,7 +173,31 @@ pub struct ResultOfRun {
 pub struct ResultOfLocalRun {
     pub output: Value,
     pub fees: Option<TransactionFees>,
-    pub account: Option<serde_json::Value>
+    pub account: Option<serde_json::Value>,
+    /// Messages produced by emulated transaction as base64 encoded BOCs. `None` if transaction
+    /// is not emulated or core doesn't report produced messages: `outMessages` is not returned
+    /// by `contracts.run.local*` of core built from the tag pinned in `Cargo.toml`
+    #[serde(default)]
+    pub out_messages: Option<Vec<String>>,
+}
+
+impl ResultOfRun {
+    /// Map function output to Rust type. See `abi_serde` module for ABI types helpers
+    pub fn output_as<T: serde::de::DeserializeOwned>(&self) -> TonResult<T> {
+        output_as(&self.output)
+    }
+}
+
+impl ResultOfLocalRun {
+    /// Map function output to Rust type. See `abi_serde` module for ABI types helpers
+    pub fn output_as<T: serde::de::DeserializeOwned>(&self) -> TonResult<T> {
+        output_as(&self.output)
+    }
+}
+
+fn output_as<T: serde::de::DeserializeOwned>(output: &Value) -> TonResult<T> {
+    serde_json::from_value(output.clone())
+        .map_err(|err| TonErrorKind::InvalidData(format!("Can not map function output {}: {}", output, err)).into())
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -139,6 +222,63 @@ pub struct EncodedMessage {
     pub address: TonAddress,
 }
 
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParamsOfEncodeUnsignedRunMessage {
+    pub address: TonAddress,
+    pub abi: serde_json::Value,
+    pub function_name: String,
+    pub header: Option<serde_json::Value>,
+    pub input: serde_json::Value,
+    pub try_index: Option<u8>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParamsOfEncodeUnsignedDeployMessage {
+    pub abi: serde_json::Value,
+    pub constructor_header: Option<serde_json::Value>,
+    pub constructor_params: serde_json::Value,
+    pub init_params: Option<serde_json::Value>,
+    pub image_base64: String,
+    pub public_key_hex: Ed25519Public,
+    pub workchain_id: i32,
+    pub try_index: Option<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResultOfEncodeUnsignedDeployMessage {
+    pub encoded: crate::json_helper::EncodedUnsignedMessageCore,
+    pub address_hex: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParamsOfEncodeWithSign {
+    pub abi: serde_json::Value,
+    pub unsigned_bytes_base64: String,
+    pub sign_bytes_base64: String,
+    pub public_key_hex: Option<Ed25519Public>,
+}
+
+/// Message without signature. Contains bytes to be signed by contract owner key and everything
+/// needed to produce the final message with `attach_signature`, so it can be serialized and moved
+/// to another machine for signing.
+///
+/// Serialized as JSON object with `unsignedBytesBase64`, `bytesToSignBase64`, `expire`, `address`
+/// and `abi` fields
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(try_from = "crate::json_helper::UnsignedMessageCore")]
+#[serde(into = "crate::json_helper::UnsignedMessageCore")]
+pub struct UnsignedMessage {
+    pub unsigned_bytes: Vec<u8>,
+    pub bytes_to_sign: Vec<u8>,
+    pub expire: Option<u32>,
+    pub address: TonAddress,
+    pub abi: serde_json::Value,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ParamsOfDecodeMessageBody {
@@ -155,6 +295,34 @@ pub struct ResultOfDecodeMessageBody {
     pub output: serde_json::Value
 }
 
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParamsOfGetRunBody {
+    pub abi: serde_json::Value,
+    pub function: String,
+    pub header: Option<serde_json::Value>,
+    pub params: serde_json::Value,
+    pub internal: bool,
+    pub key_pair: Option<Ed25519KeyPair>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResultOfGetRunBody {
+    pub body_base64: String,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParamsOfGetBocHash {
+    pub boc_base64: String,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub(crate) struct ResultOfGetBocHash {
+    pub hash: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ParamsOfProcessMessage{
@@ -204,13 +372,25 @@ pub(crate) struct ParamsOfProcessTransaction{
     pub address: TonAddress,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct MessageProcessingState {
     last_block_id: String,
     sending_time: u32,
 }
 
+impl MessageProcessingState {
+    /// Last shard block id known before message was sent. Transaction is searched starting
+    /// from this block
+    pub fn last_block_id(&self) -> &str {
+        &self.last_block_id
+    }
+
+    pub fn sending_time(&self) -> u32 {
+        self.sending_time
+    }
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ParamsOfWaitForTransaction {
@@ -224,11 +404,96 @@ pub(crate) struct ParamsOfWaitForTransaction {
 /// Contract management struct
 pub struct TonContracts {
     context: InteropContext,
+    retry_policy: RwLock<Option<RetryPolicy>>,
+    // core `message_retries_count` used when retries are performed on client side
+    message_retries_count: AtomicU8,
+    validate_input: AtomicBool,
+    // parsed ABIs used for input validation by ABI JSON
+    abi_cache: Arc<RwLock<HashMap<String, Arc<AbiInfo>>>>,
 }
 
 impl TonContracts {
     pub(crate) fn new(context: InteropContext) -> Self {
-        Self { context }
+        Self {
+            context,
+            retry_policy: RwLock::new(None),
+            message_retries_count: AtomicU8::new(DEFAULT_MESSAGE_RETRIES_COUNT),
+            validate_input: AtomicBool::new(false),
+            abi_cache: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    // instance for worker thread with the same settings sharing ABI cache
+    fn worker(&self) -> Self {
+        Self {
+            context: self.context,
+            retry_policy: RwLock::new(self.retry_policy()),
+            message_retries_count: AtomicU8::new(self.message_retries_count.load(Ordering::Relaxed)),
+            validate_input: AtomicBool::new(self.validate_input()),
+            abi_cache: self.abi_cache.clone(),
+        }
+    }
+
+    /// Enable validation and coercion of function input against ABI before calling core.
+    /// Invalid parameter is reported with its path in `InvalidInputParam` error.
+    /// Disabled by default, so input is checked by core only
+    pub fn set_validate_input(&self, validate: bool) {
+        self.validate_input.store(validate, Ordering::Relaxed);
+    }
+
+    pub fn validate_input(&self) -> bool {
+        self.validate_input.load(Ordering::Relaxed)
+    }
+
+    /// Set client-side retry policy used by `run`, `deploy` and `process_message`.
+    /// If policy is not set (default) retries are performed by core according to
+    /// `TonClientConfig` parameters
+    pub fn set_retry_policy(&self, policy: Option<RetryPolicy>) {
+        *self.retry_policy.write().unwrap_or_else(|err| err.into_inner()) = policy;
+    }
+
+    pub(crate) fn context(&self) -> InteropContext {
+        self.context
+    }
+
+    pub fn retry_policy(&self) -> Option<RetryPolicy> {
+        self.retry_policy.read().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+
+    pub(crate) fn set_message_retries_count(&self, count: u8) {
+        self.message_retries_count.store(count, Ordering::Relaxed);
+    }
+
+    /// Policy set with `set_retry_policy` or policy retrying expired messages as many times
+    /// as core does according to `message_retries_count` config parameter
+    pub(crate) fn effective_retry_policy(&self) -> RetryPolicy {
+        self.retry_policy().unwrap_or_else(|| {
+            RetryPolicy::new(self.message_retries_count.load(Ordering::Relaxed).saturating_add(1))
+        })
+    }
+
+    // Check input against ABI and convert it to the form accepted by core, so invalid parameter is
+    // reported with its path. Calls of functions missing in ABI and ABIs client can't parse are
+    // left to core to report
+    fn abi_input(&self, abi: &Value, function_name: &str, input: JsonValue) -> TonResult<Value> {
+        let input = input.to_value()?;
+        if !self.validate_input() {
+            return Ok(input);
+        }
+        match self.abi_info(abi).as_ref().and_then(|abi| abi.function(function_name)) {
+            Some(function) => function.coerce_input(&input),
+            None => Ok(input)
+        }
+    }
+
+    fn abi_info(&self, abi: &Value) -> Option<Arc<AbiInfo>> {
+        let key = abi.to_string();
+        if let Some(info) = self.abi_cache.read().unwrap_or_else(|err| err.into_inner()).get(&key) {
+            return Some(info.clone());
+        }
+        let info = Arc::new(AbiInfo::from_value(abi).ok()?);
+        self.abi_cache.write().unwrap_or_else(|err| err.into_inner()).insert(key, info.clone());
+        Some(info)
     }
 
     /// Get address for contract deploying
@@ -277,6 +542,17 @@ impl TonContracts {
         keys: &Ed25519KeyPair,
         workchain_id: i32,
     ) -> TonResult<ResultOfDeploy> {
+        if let Some(policy) = self.retry_policy() {
+            let params = DeployParams {
+                abi,
+                image: code.to_vec(),
+                constructor_header,
+                constructor_params,
+                init_params,
+                workchain_id,
+            };
+            return self.deploy_with_retry(&params, keys, &policy);
+        }
         Interop::json_request(self.context, "contracts.deploy", ParamsOfDeploy {
             abi: abi.to_value()?,
             init_params: option_params_to_value(init_params)?,
@@ -289,6 +565,136 @@ impl TonContracts {
         })
     }
 
+    /// Deploy contract to TON blockchain retrying according to `policy`
+    pub fn deploy_with_retry(
+        &self,
+        params: &DeployParams,
+        keys: &Ed25519KeyPair,
+        policy: &RetryPolicy,
+    ) -> TonResult<ResultOfDeploy> {
+        self.deploy_with_policy(params, keys, policy, &|_| {})
+    }
+
+    /// Deploy contract to TON blockchain reporting processing progress to `on_event`.
+    /// Default retry policy is applied if set, otherwise message is retried like core does
+    pub fn deploy_with_events(
+        &self,
+        params: &DeployParams,
+        keys: &Ed25519KeyPair,
+        on_event: &dyn Fn(&ProcessingEvent),
+    ) -> TonResult<ResultOfDeploy> {
+        let policy = self.effective_retry_policy();
+        self.deploy_with_policy(params, keys, &policy, on_event)
+    }
+
+    /// Deploy several contracts processing up to `parallelism` deploy messages concurrently.
+    /// Returns results in the same order as `items`. Default retry policy is applied to every
+    /// item if set, otherwise messages are retried like core does in `deploy`
+    pub fn deploy_many(&self, items: Vec<DeployItem>, parallelism: usize) -> Vec<TonResult<ResultOfDeploy>> {
+        self.deploy_many_with_events(items, parallelism, &|_, _| {})
+    }
+
+    /// Same as `deploy_many` reporting processing progress of every item to `on_event` along
+    /// with the item index. `MessageSent` state allows to resume waiting for the item which
+    /// failed with `wait_for_transaction`. Events are reported from the calling thread
+    pub fn deploy_many_with_events(
+        &self,
+        items: Vec<DeployItem>,
+        parallelism: usize,
+        on_event: &dyn Fn(usize, &ProcessingEvent),
+    ) -> Vec<TonResult<ResultOfDeploy>> {
+        let count = items.len();
+        let queue = Arc::new(Mutex::new(items.into_iter().enumerate().collect::<VecDeque<_>>()));
+        let policy = self.effective_retry_policy();
+        let (sender, receiver) = mpsc::channel();
+
+        let workers: Vec<_> = (0..parallelism.max(1).min(count))
+            .map(|_| {
+                let queue = queue.clone();
+                let sender = sender.clone();
+                let policy = policy.clone();
+                let contracts = self.worker();
+                std::thread::spawn(move || loop {
+                    let next = queue.lock().unwrap_or_else(|err| err.into_inner()).pop_front();
+                    let (index, item) = match next {
+                        Some(next) => next,
+                        None => break
+                    };
+                    let result = contracts.deploy_with_policy(
+                        &item.params,
+                        &item.keys,
+                        &policy,
+                        &|event| { let _ = sender.send(DeployProgress::Event(index, event.clone())); });
+                    if sender.send(DeployProgress::Done(index, result)).is_err() {
+                        break;
+                    }
+                })
+            })
+            .collect();
+        drop(sender);
+
+        let mut results: Vec<Option<TonResult<ResultOfDeploy>>> = (0..count).map(|_| None).collect();
+        for progress in receiver {
+            match progress {
+                DeployProgress::Event(index, event) => on_event(index, &event),
+                DeployProgress::Done(index, result) => results[index] = Some(result),
+            }
+        }
+        for worker in workers {
+            let _ = worker.join();
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(||
+                Err(TonErrorKind::InternalError("Deploy worker thread panicked".to_owned()).into())))
+            .collect()
+    }
+
+    fn deploy_with_policy(
+        &self,
+        params: &DeployParams,
+        keys: &Ed25519KeyPair,
+        policy: &RetryPolicy,
+        on_event: &dyn Fn(&ProcessingEvent),
+    ) -> TonResult<ResultOfDeploy> {
+        let address = self.get_deploy_address(
+            params.abi.clone(), &params.image, params.init_params.clone(), &keys.public, params.workchain_id)?;
+        if self.is_account_active(&address)? {
+            return Ok(ResultOfDeploy {
+                address,
+                already_deployed: true,
+                fees: None,
+                transaction: Value::Null,
+            });
+        }
+
+        policy.execute_with_notify(
+            |try_index| {
+                let message = self.create_deploy_message(
+                    params.abi.clone(),
+                    &params.image,
+                    params.constructor_header.clone(),
+                    params.constructor_params.clone(),
+                    params.init_params.clone(),
+                    keys,
+                    params.workchain_id,
+                    Some(try_index))?;
+                on_event(&ProcessingEvent::MessageEncoded {
+                    message_id: message.message_id.clone(),
+                    expire: message.expire,
+                });
+                let result = self.send_and_wait(message, Some(params.abi.clone()), None, false, on_event)?;
+                Ok(ResultOfDeploy {
+                    address: address.clone(),
+                    already_deployed: false,
+                    fees: Some(result.fees),
+                    transaction: result.transaction,
+                })
+            },
+            |retry| notify_retry(retry, on_event))
+    }
+
     /// Run the contract function with given parameters
     pub fn run(
         &self,
@@ -299,17 +705,43 @@ impl TonContracts {
         input: JsonValue,
         keys: Option<&Ed25519KeyPair>,
     ) -> TonResult<ResultOfRun> {
+        if let Some(policy) = self.retry_policy() {
+            let params = RunParams { abi, function_name: function_name.to_owned(), header, input };
+            return self.run_with_retry(address, &params, keys, &policy);
+        }
+        let abi = abi.to_value()?;
         Interop::json_request(self.context, "contracts.run", ParamsOfRun {
             address: address.clone(),
-            abi: abi.to_value()?,
+            input: self.abi_input(&abi, function_name, input)?,
+            abi,
             function_name: function_name.to_string(),
             header: option_params_to_value(header)?,
-            input: input.to_value()?,
             key_pair: keys.cloned(),
             try_index: None,
         })
     }
 
+    /// Run the contract function with given parameters retrying according to `policy`
+    pub fn run_with_retry(
+        &self,
+        address: &TonAddress,
+        params: &RunParams,
+        keys: Option<&Ed25519KeyPair>,
+        policy: &RetryPolicy,
+    ) -> TonResult<ResultOfRun> {
+        policy.execute(|try_index| {
+            let message = self.create_run_message(
+                address,
+                params.abi.clone(),
+                &params.function_name,
+                params.header.clone(),
+                params.input.clone(),
+                keys,
+                Some(try_index))?;
+            self.process_message_once(message, Some(params.abi.clone()), Some(&params.function_name), false)
+        })
+    }
+
     /// Run the contract function with given parameters locally
     pub fn run_local(
         &self,
@@ -323,13 +755,14 @@ impl TonContracts {
         context: Option<LocalRunContext>,
         emulate_transaction: bool
     ) -> TonResult<ResultOfLocalRun> {
+        let abi = abi.to_value()?;
         Interop::json_request(self.context, "contracts.run.local", ParamsOfLocalRun {
             address: address.clone(),
             account: option_params_to_value(account)?,
-            abi: abi.to_value()?,
+            input: self.abi_input(&abi, function_name, input)?,
+            abi,
             function_name: function_name.to_string(),
             header: option_params_to_value(header)?,
-            input: input.to_value()?,
             key_pair: keys.cloned(),
             context,
             full_run: emulate_transaction
@@ -402,12 +835,13 @@ impl TonContracts {
         keys: Option<&Ed25519KeyPair>,
         try_index: Option<u8>
     ) -> TonResult<EncodedMessage> {
+        let abi = abi.to_value()?;
         Interop::json_request(self.context, "contracts.run.message", ParamsOfRun {
             address: address.clone(),
-            abi: abi.to_value()?,
+            input: self.abi_input(&abi, function_name, input)?,
+            abi,
             function_name: function_name.to_string(),
             header: option_params_to_value(header)?,
-            input: input.to_value()?,
             key_pair: keys.cloned(),
             try_index,
         })
@@ -440,6 +874,113 @@ impl TonContracts {
         })
     }
 
+    /// Encode contract function call into message body without creating message. Body can be
+    /// used as internal message body or payload of another call and decoded back with
+    /// `decode_input_message_body` using the same `internal` flag. External message body is
+    /// signed with `keys` if provided
+    pub fn encode_message_body(
+        &self,
+        abi: JsonValue,
+        function_name: &str,
+        header: Option<JsonValue>,
+        input: JsonValue,
+        internal: bool,
+        keys: Option<&Ed25519KeyPair>,
+    ) -> TonResult<Vec<u8>> {
+        let abi = abi.to_value()?;
+        let result: ResultOfGetRunBody = Interop::json_request(
+            self.context,
+            "contracts.run.body",
+            ParamsOfGetRunBody {
+                params: self.abi_input(&abi, function_name, input)?,
+                abi,
+                function: function_name.to_owned(),
+                header: option_params_to_value(header)?,
+                internal,
+                key_pair: keys.cloned(),
+            })?;
+        Ok(base64::decode(&result.body_base64)?)
+    }
+
+    /// Calculate hash of the bag of cells root cell. For a message it is the message ID
+    pub fn get_boc_hash(&self, boc: &[u8]) -> TonResult<String> {
+        let result: ResultOfGetBocHash = Interop::json_request(
+            self.context,
+            "contracts.boc.hash",
+            ParamsOfGetBocHash {
+                boc_base64: base64::encode(boc),
+            })?;
+        Ok(result.hash)
+    }
+
+    /// Create unsigned message to run the contract function. Sign `bytes_to_sign` of the result
+    /// and pass the signature to `attach_signature` to get message ready for sending
+    pub fn create_unsigned_run_message(
+        &self,
+        address: &TonAddress,
+        abi: JsonValue,
+        function_name: &str,
+        header: Option<JsonValue>,
+        input: JsonValue,
+        try_index: Option<u8>
+    ) -> TonResult<UnsignedMessage> {
+        let abi = abi.to_value()?;
+        let result: crate::json_helper::EncodedUnsignedMessageCore = Interop::json_request(
+            self.context,
+            "contracts.run.encode_unsigned_message",
+            ParamsOfEncodeUnsignedRunMessage {
+                address: address.clone(),
+                abi: abi.clone(),
+                function_name: function_name.to_string(),
+                header: option_params_to_value(header)?,
+                input: self.abi_input(&abi, function_name, input)?,
+                try_index,
+            })?;
+        result.into_unsigned_message(address.clone(), abi)
+    }
+
+    /// Create unsigned message to deploy contract. Contract address is calculated from `public_key`
+    pub fn create_unsigned_deploy_message(
+        &self,
+        params: &DeployParams,
+        public_key: &Ed25519Public,
+        try_index: Option<u8>
+    ) -> TonResult<UnsignedMessage> {
+        let abi = params.abi.clone().to_value()?;
+        let result: ResultOfEncodeUnsignedDeployMessage = Interop::json_request(
+            self.context,
+            "contracts.deploy.encode_unsigned_message",
+            ParamsOfEncodeUnsignedDeployMessage {
+                abi: abi.clone(),
+                init_params: option_params_to_value(params.init_params.clone())?,
+                constructor_header: option_params_to_value(params.constructor_header.clone())?,
+                constructor_params: params.constructor_params.clone().to_value()?,
+                image_base64: base64::encode(&params.image),
+                public_key_hex: public_key.clone(),
+                workchain_id: params.workchain_id,
+                try_index,
+            })?;
+        result.encoded.into_unsigned_message(TonAddress::from_str(&result.address_hex)?, abi)
+    }
+
+    /// Add signature to unsigned message and get message ready for sending
+    pub fn attach_signature(
+        &self,
+        message: &UnsignedMessage,
+        signature: &[u8],
+        public_key: Option<&Ed25519Public>,
+    ) -> TonResult<EncodedMessage> {
+        Interop::json_request(
+            self.context,
+            "contracts.encode_message_with_sign",
+            ParamsOfEncodeWithSign {
+                abi: message.abi.clone(),
+                unsigned_bytes_base64: base64::encode(&message.unsigned_bytes),
+                sign_bytes_base64: base64::encode(signature),
+                public_key_hex: public_key.cloned(),
+            })
+    }
+
     /// Send message to node without waiting for processing result
     pub fn send_message(&self, message: EncodedMessage) -> TonResult<MessageProcessingState> {
         Interop::json_request(
@@ -449,13 +990,76 @@ impl TonContracts {
         )
     }
 
-    /// Send message to waiting for processing result and (optionally) parse result
+    /// Send message and wait for processing result and (optionally) parse result
     pub fn process_message(
         &self,
         message: EncodedMessage,
         abi: Option<JsonValue>,
         function_name: Option<&str>,
         infinite_wait: bool
+    ) -> TonResult<ResultOfRun> {
+        match self.retry_policy() {
+            Some(policy) => self.process_message_with_retry(message, abi, function_name, infinite_wait, &policy),
+            None => self.process_message_once(message, abi, function_name, infinite_wait)
+        }
+    }
+
+    /// Send message and wait for processing result retrying according to `policy`.
+    /// The same message is sent on every attempt, so expired message is not retried
+    pub fn process_message_with_retry(
+        &self,
+        message: EncodedMessage,
+        abi: Option<JsonValue>,
+        function_name: Option<&str>,
+        infinite_wait: bool,
+        policy: &RetryPolicy,
+    ) -> TonResult<ResultOfRun> {
+        policy.execute_filtered(
+            |_| self.process_message_once(message.clone(), abi.clone(), function_name, infinite_wait),
+            |_| {},
+            |err| !has_error_code(err, MESSAGE_EXPIRED_CODE))
+    }
+
+    /// Send message and wait for processing result reporting progress to `on_event`.
+    /// Default retry policy is applied if set, otherwise retries configured for core are used.
+    /// The same message is sent on every attempt, so expired message is not retried
+    pub fn process_message_with_events(
+        &self,
+        message: EncodedMessage,
+        abi: Option<JsonValue>,
+        function_name: Option<&str>,
+        infinite_wait: bool,
+        on_event: &dyn Fn(&ProcessingEvent),
+    ) -> TonResult<ResultOfRun> {
+        let policy = self.effective_retry_policy();
+        policy.execute_filtered(
+            |_| self.send_and_wait(message.clone(), abi.clone(), function_name, infinite_wait, on_event),
+            |retry| notify_retry(retry, on_event),
+            |err| !has_error_code(err, MESSAGE_EXPIRED_CODE))
+    }
+
+    fn send_and_wait(
+        &self,
+        message: EncodedMessage,
+        abi: Option<JsonValue>,
+        function_name: Option<&str>,
+        infinite_wait: bool,
+        on_event: &dyn Fn(&ProcessingEvent),
+    ) -> TonResult<ResultOfRun> {
+        let state = self.send_message(message.clone())?;
+        on_event(&ProcessingEvent::MessageSent {
+            message_id: message.message_id.clone(),
+            state: state.clone(),
+        });
+        self.wait_for_transaction_with_events(message, abi, function_name, state, infinite_wait, on_event)
+    }
+
+    fn process_message_once(
+        &self,
+        message: EncodedMessage,
+        abi: Option<JsonValue>,
+        function_name: Option<&str>,
+        infinite_wait: bool
     ) -> TonResult<ResultOfRun> {
         Interop::json_request(
             self.context,
@@ -491,6 +1095,91 @@ impl TonContracts {
         )
     }
 
+    /// Wait for message processing result reporting progress to `on_event`
+    pub fn wait_for_transaction_with_events(
+        &self,
+        message: EncodedMessage,
+        abi: Option<JsonValue>,
+        function_name: Option<&str>,
+        message_processing_state: MessageProcessingState,
+        infinite_wait: bool,
+        on_event: &dyn Fn(&ProcessingEvent),
+    ) -> TonResult<ResultOfRun> {
+        let message_id = message.message_id.clone();
+        let message_processing_state = self.fetch_message_blocks(&message, message_processing_state, on_event);
+        let result = self.wait_for_transaction(
+            message, abi, function_name, message_processing_state, infinite_wait);
+
+        match &result {
+            Ok(result) => {
+                on_event(&ProcessingEvent::TransactionReceived {
+                    message_id,
+                    transaction: result.transaction.clone(),
+                });
+            }
+            Err(err) if has_error_code(err, MESSAGE_EXPIRED_CODE) => {
+                let state = match err.kind() {
+                    TonErrorKind::InnerSdkError(err) => err.message_processing_state.clone(),
+                    _ => None
+                };
+                on_event(&ProcessingEvent::Expired { message_id, state });
+            }
+            Err(_) => {}
+        }
+
+        result
+    }
+
+    // Follows shard blocks produced after the message was sent reporting `BlockFetched` for each
+    // of them until the block with the message or the block generated after message expiration
+    // is fetched. Returns state pointing to the block preceding it, so core finds the transaction
+    // or expiration there. Messages without expiration time are left to core completely
+    fn fetch_message_blocks(
+        &self,
+        message: &EncodedMessage,
+        mut state: MessageProcessingState,
+        on_event: &dyn Fn(&ProcessingEvent),
+    ) -> MessageProcessingState {
+        let expire = match message.expire {
+            Some(expire) => expire,
+            None => return state
+        };
+        let blocks = TonQueriesCollection::new(self.context, "blocks");
+        loop {
+            let filter = serde_json::json!({
+                "prev_ref": { "root_hash": { "eq": state.last_block_id } },
+                "OR": { "prev_alt_ref": { "root_hash": { "eq": state.last_block_id } } }
+            });
+            // core reports waiting errors itself
+            let block = match blocks.wait_for(filter.into(), "id gen_utime in_msg_descr { msg_id }") {
+                Ok(block) => block,
+                Err(_) => return state
+            };
+            let block_id = match block["id"].as_str() {
+                Some(id) => id.to_owned(),
+                None => return state
+            };
+            let fetched = MessageProcessingState {
+                last_block_id: block_id.clone(),
+                sending_time: state.sending_time,
+            };
+            on_event(&ProcessingEvent::BlockFetched {
+                message_id: message.message_id.clone(),
+                block_id,
+                state: fetched.clone(),
+            });
+
+            let has_message = block["in_msg_descr"].as_array()
+                .map(|messages| messages.iter().any(|msg| msg["msg_id"] == message.message_id.as_str()))
+                .unwrap_or(false);
+            let expired = block["gen_utime"].as_u64().map(|time| time > expire as u64).unwrap_or(true);
+            if has_message || expired {
+                return state;
+            }
+            state = fetched;
+        }
+    }
+
     /// Run the contract get method locally
     pub fn run_get(
         &self,
@@ -515,18 +1204,38 @@ impl TonContracts {
         })
     }
 
-    /// Convert list in `cons` representation to `Vec`
+    /// Convert list in `cons` representation to `Vec`. Same as `cons_to_vec` free function
     pub fn cons_to_vec(&self, cons: Value) -> TonResult<Value> {
-        let mut result = vec![];
-        let mut item = cons;
-        while !item.is_null() {
-            if item.as_array().unwrap_or(&vec![]).len() != 2 {
-                return Err(TonErrorKind::InvalidArg("Invalid cons".to_owned()).into());
-            }
-            result.push(item[0].take());
-            item = item[1].take();
-        }
-        Ok(result.into())
+        crate::cons_to_vec(cons)
+    }
+
+    pub(crate) fn is_account_active(&self, address: &TonAddress) -> TonResult<bool> {
+        let accounts = self.query(
+            "accounts",
+            serde_json::json!({
+                "id": { "eq": address.to_string() },
+                "acc_type": { "eq": 1 }
+            }),
+            "id",
+            Some(1))?;
+        Ok(!accounts.is_empty())
+    }
+
+    pub(crate) fn query(
+        &self,
+        table: &str,
+        filter: Value,
+        result: &str,
+        limit: Option<usize>,
+    ) -> TonResult<Vec<Value>> {
+        let result: ResultOfQuery = Interop::json_request(self.context, "queries.query", ParamsOfQuery {
+            table: table.to_owned(),
+            filter: filter.to_string(),
+            result: result.to_owned(),
+            order: None,
+            limit,
+        })?;
+        Ok(result.result)
     }
 
     /// Investigate message processing error
@@ -563,3 +1272,12 @@ impl TonContracts {
         })
     }
 }
+
+
+fn notify_retry(retry: &RetryAttempt, on_event: &dyn Fn(&ProcessingEvent)) {
+    on_event(&ProcessingEvent::Retry {
+        attempt: retry.attempt,
+        error: retry.error.to_string(),
+        delay: retry.delay,
+    })
+}
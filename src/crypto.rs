@@ -70,6 +70,19 @@ impl NaclSignKeyPair {
     }
 }
 
+#[derive(Serialize)]
+struct InputMessage {
+    base64: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ParamsOfNaclSign {
+    message: InputMessage,
+    key: String,
+    output_encoding: &'static str,
+}
+
 /// Crypto functions struct
 pub struct TonCrypto {
     context: u32,
@@ -84,6 +97,16 @@ impl TonCrypto {
     pub fn generate_ed25519_keys(&self) -> TonResult<Ed25519KeyPair> {
         Interop::json_request_no_args(self.context, "crypto.ed25519.keypair")
     }
+
+    /// Sign data with Ed25519 key pair and return detached signature
+    pub fn sign_detached(&self, data: &[u8], keys: &Ed25519KeyPair) -> TonResult<Vec<u8>> {
+        let signature: String = Interop::json_request(self.context, "crypto.nacl.sign.detached", ParamsOfNaclSign {
+            message: InputMessage { base64: base64::encode(data) },
+            key: hex::encode(&keys.to_bytes()[..]),
+            output_encoding: "Hex",
+        })?;
+        Ok(hex::decode(signature)?)
+    }
 }
 
 impl Default for HDPublic {
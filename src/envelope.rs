@@ -0,0 +1,246 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{EncodedMessage, JsonValue, TonAddress};
+use crate::error::*;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+use std::path::Path;
+
+/// Current version of message envelope format
+pub const MESSAGE_ENVELOPE_VERSION: u32 = 1;
+
+const BINARY_ENVELOPE_MAGIC: &[u8] = b"TONMSG";
+
+/// Message envelope file format
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnvelopeFormat {
+    Json,
+    Binary,
+}
+
+/// Versioned container for `EncodedMessage` with information needed to audit and replay it:
+/// hash of the contract ABI, called function name and envelope creation time.
+/// Message expiration time is stored in the message itself
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageEnvelope {
+    pub version: u32,
+    pub message: EncodedMessage,
+    pub abi_hash: Option<String>,
+    pub function_name: Option<String>,
+    pub created_at: u32,
+}
+
+impl MessageEnvelope {
+    pub fn new(message: EncodedMessage, abi: Option<&JsonValue>, function_name: Option<&str>) -> TonResult<Self> {
+        Ok(MessageEnvelope {
+            version: MESSAGE_ENVELOPE_VERSION,
+            message,
+            abi_hash: abi.map(abi_hash).transpose()?,
+            function_name: function_name.map(|name| name.to_owned()),
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_err(|err| TonErrorKind::InternalError(err.to_string()))?
+                .as_secs() as u32,
+        })
+    }
+
+    /// Check if message was created with given ABI. Returns `false` if envelope has no ABI hash
+    pub fn matches_abi(&self, abi: &JsonValue) -> TonResult<bool> {
+        Ok(self.abi_hash.as_ref() == Some(&abi_hash(abi)?))
+    }
+
+    pub fn to_json(&self) -> TonResult<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> TonResult<Self> {
+        let envelope: MessageEnvelope = serde_json::from_str(json)?;
+        check_version(envelope.version)?;
+        Ok(envelope)
+    }
+
+    /// Serialize envelope into binary format: magic bytes, version and length prefixed fields
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = BINARY_ENVELOPE_MAGIC.to_vec();
+        bytes.extend_from_slice(&self.version.to_be_bytes());
+        write_field(&mut bytes, self.message.message_id.as_bytes());
+        write_field(&mut bytes, &self.message.message_body);
+        write_optional_field(&mut bytes, self.message.expire.map(|expire| expire.to_be_bytes().to_vec()));
+        write_field(&mut bytes, self.message.address.to_string().as_bytes());
+        write_optional_field(&mut bytes, self.abi_hash.as_ref().map(|hash| hash.as_bytes().to_vec()));
+        write_optional_field(&mut bytes, self.function_name.as_ref().map(|name| name.as_bytes().to_vec()));
+        bytes.extend_from_slice(&self.created_at.to_be_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> TonResult<Self> {
+        if !bytes.starts_with(BINARY_ENVELOPE_MAGIC) {
+            return Err(TonErrorKind::InvalidData("Not a binary message envelope".to_owned()).into());
+        }
+        let mut reader = FieldReader { data: &bytes[BINARY_ENVELOPE_MAGIC.len()..] };
+
+        let version = reader.read_u32()?;
+        check_version(version)?;
+
+        let message_id = reader.read_string()?;
+        let message_body = reader.read_field()?.to_vec();
+        let expire = reader.read_optional_field()?
+            .map(|expire| <[u8; 4]>::try_from(expire).map(u32::from_be_bytes))
+            .transpose()?;
+        let address = TonAddress::from_str(&reader.read_string()?)?;
+        let abi_hash = reader.read_optional_field()?.map(utf8).transpose()?;
+        let function_name = reader.read_optional_field()?.map(utf8).transpose()?;
+        let created_at = reader.read_u32()?;
+
+        Ok(MessageEnvelope {
+            version,
+            message: EncodedMessage { message_id, message_body, expire, address },
+            abi_hash,
+            function_name,
+            created_at,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>, format: EnvelopeFormat) -> TonResult<()> {
+        let content = match format {
+            EnvelopeFormat::Json => self.to_json()?.into_bytes(),
+            EnvelopeFormat::Binary => self.to_bytes(),
+        };
+        Ok(std::fs::write(path, content)?)
+    }
+
+    /// Load envelope from file. Format is detected automatically
+    pub fn load(path: impl AsRef<Path>) -> TonResult<Self> {
+        let content = std::fs::read(path)?;
+        if content.starts_with(BINARY_ENVELOPE_MAGIC) {
+            Self::from_bytes(&content)
+        } else {
+            Self::from_json(&utf8(&content)?)
+        }
+    }
+}
+
+impl EncodedMessage {
+    /// Save message into versioned envelope file
+    pub fn to_file(
+        &self,
+        path: impl AsRef<Path>,
+        abi: Option<&JsonValue>,
+        function_name: Option<&str>,
+        format: EnvelopeFormat,
+    ) -> TonResult<()> {
+        MessageEnvelope::new(self.clone(), abi, function_name)?.save(path, format)
+    }
+
+    /// Load message from envelope file. Use `MessageEnvelope::load` to get envelope information
+    pub fn from_file(path: impl AsRef<Path>) -> TonResult<EncodedMessage> {
+        Ok(MessageEnvelope::load(path)?.message)
+    }
+}
+
+/// Calculate hex encoded SHA256 hash of ABI JSON with sorted keys
+pub fn abi_hash(abi: &JsonValue) -> TonResult<String> {
+    let mut canonical = String::new();
+    write_canonical_json(&abi.clone().to_value()?, &mut canonical)?;
+    Ok(hex::encode(Sha256::digest(canonical.as_bytes())))
+}
+
+// compact JSON with object keys sorted explicitly, so the result doesn't depend on
+// `serde_json` map ordering features
+fn write_canonical_json(value: &Value, output: &mut String) -> TonResult<()> {
+    match value {
+        Value::Array(items) => {
+            output.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                write_canonical_json(item, output)?;
+            }
+            output.push(']');
+        }
+        Value::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+            output.push('{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+                output.push_str(&serde_json::to_string(key)?);
+                output.push(':');
+                write_canonical_json(&object[key], output)?;
+            }
+            output.push('}');
+        }
+        _ => output.push_str(&serde_json::to_string(value)?)
+    }
+    Ok(())
+}
+
+fn check_version(version: u32) -> TonResult<()> {
+    if version == 0 || version > MESSAGE_ENVELOPE_VERSION {
+        return Err(TonErrorKind::InvalidData(
+            format!("Unsupported message envelope version {}", version)).into());
+    }
+    Ok(())
+}
+
+fn utf8(bytes: &[u8]) -> TonResult<String> {
+    String::from_utf8(bytes.to_vec())
+        .map_err(|err| TonErrorKind::InvalidData(format!("Invalid UTF-8 string: {}", err)).into())
+}
+
+fn write_field(bytes: &mut Vec<u8>, field: &[u8]) {
+    bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(field);
+}
+
+fn write_optional_field(bytes: &mut Vec<u8>, field: Option<Vec<u8>>) {
+    match field {
+        Some(field) => {
+            bytes.push(1);
+            write_field(bytes, &field);
+        }
+        None => bytes.push(0),
+    }
+}
+
+struct FieldReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> FieldReader<'a> {
+    fn read_bytes(&mut self, count: usize) -> TonResult<&'a [u8]> {
+        if count > self.data.len() {
+            return Err(TonErrorKind::InvalidData("Unexpected end of message envelope".to_owned()).into());
+        }
+        let (bytes, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> TonResult<u32> {
+        Ok(u32::from_be_bytes(<[u8; 4]>::try_from(self.read_bytes(4)?)?))
+    }
+
+    fn read_field(&mut self) -> TonResult<&'a [u8]> {
+        let len = self.read_u32()? as usize;
+        self.read_bytes(len)
+    }
+
+    fn read_optional_field(&mut self) -> TonResult<Option<&'a [u8]>> {
+        match self.read_bytes(1)?[0] {
+            0 => Ok(None),
+            _ => self.read_field().map(Some)
+        }
+    }
+
+    fn read_string(&mut self) -> TonResult<String> {
+        utf8(self.read_field()?)
+    }
+}
@@ -3,7 +3,7 @@
  */
 
  /// Error returned from SDK core
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct InnerSdkError {
     pub core_version: String,
     pub source: String,
@@ -51,6 +51,10 @@ error_chain! {
             description("Invalid argument"),
             display("Invalid argument: {}", msg)
         }
+        InvalidInputParam(path: String, msg: String) {
+            description("Invalid function input parameter"),
+            display("Invalid input parameter `{}`: {}", path, msg)
+        }
         InvalidFunctionParams(func: String, inner: String){
             description("Invalid function parameters"),
             display("Can not serialize params for {}. Error {}", func, inner)
@@ -0,0 +1,341 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{JsonValue, OrderBy, SortDirection, TonAddress, TonClient, TonContracts, TonQueriesCollection};
+use crate::error::*;
+use crate::queries::StreamHandle;
+use futures::stream::Stream;
+use futures::{Async, Poll};
+use serde_json::Value;
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+// `msg_type` of external outbound messages in GraphQL API
+pub(crate) const EXT_OUT_MESSAGE_TYPE: u8 = 2;
+
+const EVENT_MESSAGE_FIELDS: &str = "id msg_type body created_at";
+
+const DEFAULT_RECONNECT_ATTEMPTS: u8 = 5;
+
+// number of last message ids kept to skip duplicates
+const DEDUP_WINDOW: usize = 1000;
+
+/// Contract event decoded from external outbound message
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedEvent {
+    pub name: String,
+    pub params: Value,
+    /// Id of the message carrying event
+    pub message_id: String,
+}
+
+impl TonContracts {
+    /// Decode events emitted by transaction. Function returns and messages not matching ABI events
+    /// are skipped. Transaction output messages are taken from `out_messages` field if present,
+    /// otherwise they are queried by ids from `out_msgs`
+    pub fn decode_events(&self, abi: JsonValue, transaction: &Value) -> TonResult<Vec<DecodedEvent>> {
+        let abi = abi.to_value()?;
+        let event_names = abi_event_names(&abi);
+        if event_names.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let messages = match transaction["out_messages"].as_array() {
+            Some(messages) => messages.clone(),
+            None => {
+                let ids = transaction["out_msgs"].as_array().cloned().unwrap_or_default();
+                if ids.is_empty() {
+                    vec![]
+                } else {
+                    let messages = self.query(
+                        "messages",
+                        serde_json::json!({ "id": { "in": ids } }),
+                        "id msg_type body",
+                        None)?;
+                    order_by_ids(messages, &ids)
+                }
+            }
+        };
+
+        let mut events = vec![];
+        for message in &messages {
+            if let Some(event) = self.decode_event_message(&abi, &event_names, message)? {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    pub(crate) fn decode_event_message(
+        &self,
+        abi: &Value,
+        event_names: &[String],
+        message: &Value,
+    ) -> TonResult<Option<DecodedEvent>> {
+        if message["msg_type"].as_u64() != Some(EXT_OUT_MESSAGE_TYPE as u64) {
+            return Ok(None);
+        }
+        let body = match message["body"].as_str() {
+            Some(body) => base64::decode(body)?,
+            None => return Ok(None)
+        };
+
+        // body not matching ABI is not an event of this contract
+        let decoded = match self.decode_output_message_body(abi.clone().into(), &body) {
+            Ok(decoded) => decoded,
+            Err(_) => return Ok(None)
+        };
+        if !event_names.contains(&decoded.function) {
+            return Ok(None);
+        }
+
+        Ok(Some(DecodedEvent {
+            name: decoded.function,
+            params: decoded.output,
+            message_id: message["id"].as_str().unwrap_or_default().to_owned(),
+        }))
+    }
+}
+
+impl TonContracts {
+    /// Subscribe for events emitted by contract with given address. Only events from
+    /// `event_names` are reported if set. Returns `Stream` of decoded events which resubscribes
+    /// after connection errors and skips already reported messages
+    pub fn subscribe_events<'a>(
+        &'a self,
+        address: &TonAddress,
+        abi: JsonValue,
+        event_names: Option<&[&str]>,
+    ) -> TonResult<EventStream<'a>> {
+        let abi = abi.to_value()?;
+        let mut abi_events = abi_event_names(&abi);
+        if let Some(names) = event_names {
+            if let Some(unknown) = names.iter().find(|name| !abi_events.iter().any(|event| event == *name)) {
+                return Err(TonErrorKind::InvalidArg(format!("Event `{}` not found in ABI", unknown)).into());
+            }
+            abi_events.retain(|event| names.contains(&event.as_str()));
+        }
+
+        let messages = TonQueriesCollection::new(self.context(), "messages");
+        let filter = serde_json::json!({
+            "src": { "eq": address.to_string() },
+            "msg_type": { "eq": EXT_OUT_MESSAGE_TYPE }
+        });
+        let handle = messages.subscribe_handle(filter.clone().into(), EVENT_MESSAGE_FIELDS)?;
+
+        Ok(EventStream {
+            contracts: self,
+            messages,
+            handle: Some(handle),
+            filter,
+            abi,
+            event_names: abi_events,
+            pending: VecDeque::new(),
+            seen: SeenMessages::new(DEDUP_WINDOW),
+            reconnect_attempts: DEFAULT_RECONNECT_ATTEMPTS,
+            reconnect_delay: Duration::from_secs(1),
+            failed_attempts: 0,
+            reconnect_at: None,
+            wakeup_at: None,
+        })
+    }
+}
+
+impl TonClient {
+    /// Subscribe for events emitted by contract with given address. Returns `Stream` of decoded
+    /// events
+    #[deprecated(note = "use `TonContracts::subscribe_events` returning `EventStream`")]
+    pub fn subscribe_events<'a>(
+        &'a self,
+        address: &TonAddress,
+        abi: JsonValue,
+    ) -> TonResult<Box<dyn Stream<Item=DecodedEvent, Error=TonError> + 'a>> {
+        Ok(Box::new(self.contracts.subscribe_events(address, abi, None)?))
+    }
+}
+
+/// Stream of decoded contract events returned by `subscribe_events`.
+///
+/// If subscription fails, stream subscribes again and queries messages created since the last
+/// received one, so events emitted during reconnection are not lost. Stream fails only after
+/// `reconnect_attempts` consecutive errors
+pub struct EventStream<'a> {
+    contracts: &'a TonContracts,
+    messages: TonQueriesCollection,
+    handle: Option<StreamHandle>,
+    filter: Value,
+    abi: Value,
+    event_names: Vec<String>,
+    pending: VecDeque<Value>,
+    seen: SeenMessages,
+    reconnect_attempts: u8,
+    reconnect_delay: Duration,
+    failed_attempts: u8,
+    reconnect_at: Option<Instant>,
+    // reconnect time the wakeup thread is already spawned for
+    wakeup_at: Option<Instant>,
+}
+
+impl<'a> EventStream<'a> {
+    pub fn reconnect_attempts(mut self, attempts: u8) -> Self {
+        self.reconnect_attempts = attempts;
+        self
+    }
+
+    pub fn reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    fn next_message(&mut self) -> TonResult<Value> {
+        if let Some(message) = self.pending.pop_front() {
+            return Ok(message);
+        }
+        match self.handle {
+            Some(handle) => self.messages.get_next(handle),
+            None => Err(TonErrorKind::InternalError("Event subscription is not active".to_owned()).into())
+        }
+    }
+
+    // drop broken subscription and schedule resubscription after `reconnect_delay`. Stream is
+    // woken up by a timer thread, so `poll` never blocks waiting for reconnection
+    fn schedule_reconnect(&mut self, err: TonError) -> TonResult<()> {
+        if self.failed_attempts >= self.reconnect_attempts {
+            return Err(err);
+        }
+        self.failed_attempts += 1;
+        self.unsubscribe();
+        self.reconnect_at = Some(Instant::now() + self.reconnect_delay);
+        Ok(())
+    }
+
+    fn reconnect(&mut self) -> TonResult<()> {
+        self.handle = Some(self.messages.subscribe_handle(self.filter.clone().into(), EVENT_MESSAGE_FIELDS)?);
+
+        // fetch messages which could be created while subscription was broken
+        if let Some(last_created_at) = self.seen.last_created_at {
+            let mut filter = self.filter.clone();
+            filter["created_at"] = serde_json::json!({ "ge": last_created_at });
+            let missed = self.messages.query(
+                filter.into(),
+                EVENT_MESSAGE_FIELDS,
+                Some(OrderBy { path: "created_at".to_owned(), direction: SortDirection::Ascending }),
+                None)?;
+            self.pending.extend(missed);
+        }
+        Ok(())
+    }
+
+    fn unsubscribe(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            // subscription may be already closed by core after error
+            let _ = self.messages.unsubscribe(handle);
+        }
+    }
+}
+
+impl<'a> Drop for EventStream<'a> {
+    fn drop(&mut self) {
+        self.unsubscribe();
+    }
+}
+
+impl<'a> Stream for EventStream<'a> {
+    type Item = DecodedEvent;
+    type Error = TonError;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            if let Some(reconnect_at) = self.reconnect_at {
+                let now = Instant::now();
+                if now < reconnect_at {
+                    if self.wakeup_at != Some(reconnect_at) {
+                        self.wakeup_at = Some(reconnect_at);
+                        let task = futures::task::current();
+                        let delay = reconnect_at - now;
+                        std::thread::spawn(move || {
+                            std::thread::sleep(delay);
+                            task.notify();
+                        });
+                    }
+                    return Ok(Async::NotReady);
+                }
+                self.reconnect_at = None;
+                if let Err(err) = self.reconnect() {
+                    self.schedule_reconnect(err)?;
+                    continue;
+                }
+            }
+
+            let message = match self.next_message() {
+                Ok(message) => message,
+                Err(err) => {
+                    self.schedule_reconnect(err)?;
+                    continue;
+                }
+            };
+            self.failed_attempts = 0;
+            if !self.seen.insert(&message) {
+                continue;
+            }
+            if let Some(event) = self.contracts.decode_event_message(&self.abi, &self.event_names, &message)? {
+                return Ok(Async::Ready(Some(event)));
+            }
+        }
+    }
+}
+
+/// Ids of the last received messages used to skip duplicates after resubscription
+pub(crate) struct SeenMessages {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+    capacity: usize,
+    pub last_created_at: Option<u64>,
+}
+
+impl SeenMessages {
+    pub fn new(capacity: usize) -> Self {
+        SeenMessages {
+            ids: HashSet::new(),
+            order: VecDeque::new(),
+            capacity,
+            last_created_at: None,
+        }
+    }
+
+    /// Returns `false` if message was already received
+    pub fn insert(&mut self, message: &Value) -> bool {
+        let id = match message["id"].as_str() {
+            Some(id) => id.to_owned(),
+            None => return true
+        };
+        if !self.ids.insert(id.clone()) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        if let Some(created_at) = message["created_at"].as_u64() {
+            self.last_created_at = Some(std::cmp::max(created_at, self.last_created_at.unwrap_or(0)));
+        }
+        true
+    }
+}
+
+// Query returns messages in arbitrary order, events must follow transaction `out_msgs` order
+pub(crate) fn order_by_ids(mut messages: Vec<Value>, ids: &[Value]) -> Vec<Value> {
+    messages.sort_by_key(|message| ids.iter().position(|id| *id == message["id"]));
+    messages
+}
+
+pub(crate) fn abi_event_names(abi: &Value) -> Vec<String> {
+    abi["events"].as_array()
+        .map(|events| events.iter()
+            .filter_map(|event| event["name"].as_str().map(|name| name.to_owned()))
+            .collect())
+        .unwrap_or_default()
+}
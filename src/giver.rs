@@ -0,0 +1,81 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{Ed25519KeyPair, Grams, ResultOfRun, TonAddress, TonClient};
+use crate::error::*;
+
+/// Address of the giver contract preinstalled in TON OS Startup Edition (Node SE)
+pub const NODE_SE_GIVER_ADDRESS: &str = "0:841288ed3b55d9cdafa806807f02a0ae0c169aa5edfe88a789a6482429756a94";
+
+const NODE_SE_GIVER_ABI: &str = include_str!("contracts/Giver.abi.json");
+const GIVER_WALLET_ABI: &str = include_str!("contracts/GiverWallet.abi.json");
+
+/// Source of tokens used to fund accounts before deploy
+pub trait Giver {
+    /// Send `value` nanograms to `dest`. Function returns after giver transaction is
+    /// processed, use `TonClient::fund_account` to also wait for tokens receiving
+    fn give(&self, client: &TonClient, dest: &TonAddress, value: Grams) -> TonResult<ResultOfRun>;
+}
+
+/// Node SE giver contract which sends tokens without signature
+pub struct NodeSeGiver {
+    pub address: TonAddress,
+}
+
+impl NodeSeGiver {
+    pub fn new() -> Self {
+        NodeSeGiver {
+            address: TonAddress::from_str(NODE_SE_GIVER_ADDRESS).expect("Invalid Node SE giver address"),
+        }
+    }
+}
+
+impl Default for NodeSeGiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Giver for NodeSeGiver {
+    fn give(&self, client: &TonClient, dest: &TonAddress, value: Grams) -> TonResult<ResultOfRun> {
+        client.contracts.run(
+            &self.address,
+            NODE_SE_GIVER_ABI.into(),
+            "sendGrams",
+            None,
+            serde_json::json!({
+                "dest": dest.to_string(),
+                "amount": value,
+            }).into(),
+            None)
+    }
+}
+
+/// Wallet contract with `GiverWallet` ABI sending tokens with `sendTransaction` signed by `keys`
+pub struct WalletGiver {
+    pub address: TonAddress,
+    pub keys: Ed25519KeyPair,
+}
+
+impl WalletGiver {
+    pub fn new(address: TonAddress, keys: Ed25519KeyPair) -> Self {
+        WalletGiver { address, keys }
+    }
+}
+
+impl Giver for WalletGiver {
+    fn give(&self, client: &TonClient, dest: &TonAddress, value: Grams) -> TonResult<ResultOfRun> {
+        client.contracts.run(
+            &self.address,
+            GIVER_WALLET_ABI.into(),
+            "sendTransaction",
+            None,
+            serde_json::json!({
+                "dest": dest.to_string(),
+                "value": value,
+                "bounce": false,
+            }).into(),
+            Some(&self.keys))
+    }
+}
@@ -0,0 +1,193 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Visitor;
+use std::convert::TryFrom;
+use crate::error::*;
+
+/// Number of decimal places in one token (1 token = 10^9 nanograms)
+pub const GRAMS_DECIMALS: usize = 9;
+
+const NANOGRAMS_IN_TOKEN: u128 = 1_000_000_000;
+
+/// Token amount in nanograms.
+///
+/// Serialized as decimal string which is accepted by ABI `uint128` (`gram`) parameters.
+/// Deserialized from decimal or `0x` prefixed hex string or from JSON number.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Grams(pub u128);
+
+impl Grams {
+    pub fn zero() -> Grams {
+        Grams(0)
+    }
+
+    pub fn from_nano(nanograms: u128) -> Grams {
+        Grams(nanograms)
+    }
+
+    /// Create amount from whole tokens count
+    pub fn from_tokens(tokens: u64) -> TonResult<Grams> {
+        (tokens as u128).checked_mul(NANOGRAMS_IN_TOKEN)
+            .map(Grams)
+            .ok_or_else(|| TonErrorKind::InvalidArg(format!("Tokens amount overflow: {}", tokens)).into())
+    }
+
+    pub fn as_nano(&self) -> u128 {
+        self.0
+    }
+
+    /// Parses tokens amount written as decimal fraction (e.g. `1.5`). Up to 9 decimal places
+    /// are allowed
+    pub fn from_tokens_str(string: &str) -> TonResult<Grams> {
+        let string = string.trim();
+        let invalid = || -> TonError {
+            TonErrorKind::InvalidData(format!("Error parsing tokens amount: {}", string)).into()
+        };
+
+        let (whole, fraction) = match string.find('.') {
+            Some(pos) => (&string[..pos], &string[pos + 1..]),
+            None => (string, "")
+        };
+        if whole.is_empty() && fraction.is_empty()
+            || fraction.len() > GRAMS_DECIMALS
+            || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let whole = if whole.is_empty() { 0 } else { whole.parse::<u128>().map_err(|_| invalid())? };
+        let fraction = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse::<u128>().map_err(|_| invalid())?
+                * 10u128.pow((GRAMS_DECIMALS - fraction.len()) as u32)
+        };
+
+        whole.checked_mul(NANOGRAMS_IN_TOKEN)
+            .and_then(|whole| whole.checked_add(fraction))
+            .map(Grams)
+            .ok_or_else(invalid)
+    }
+
+    /// Formats amount in tokens with given number of decimal places. Extra digits are truncated
+    pub fn to_tokens_string(&self, decimals: usize) -> String {
+        let whole = self.0 / NANOGRAMS_IN_TOKEN;
+        if decimals == 0 {
+            return whole.to_string();
+        }
+        let fraction = format!("{:09}", self.0 % NANOGRAMS_IN_TOKEN);
+        let fraction = if decimals <= GRAMS_DECIMALS {
+            fraction[..decimals].to_owned()
+        } else {
+            fraction + &"0".repeat(decimals - GRAMS_DECIMALS)
+        };
+        format!("{}.{}", whole, fraction)
+    }
+
+    pub fn checked_add(self, other: Grams) -> Option<Grams> {
+        self.0.checked_add(other.0).map(Grams)
+    }
+
+    pub fn checked_sub(self, other: Grams) -> Option<Grams> {
+        self.0.checked_sub(other.0).map(Grams)
+    }
+
+    pub fn checked_mul(self, multiplier: u128) -> Option<Grams> {
+        self.0.checked_mul(multiplier).map(Grams)
+    }
+
+    pub fn checked_div(self, divider: u128) -> Option<Grams> {
+        self.0.checked_div(divider).map(Grams)
+    }
+}
+
+impl From<u64> for Grams {
+    fn from(value: u64) -> Self {
+        Grams(value as u128)
+    }
+}
+
+impl From<u128> for Grams {
+    fn from(value: u128) -> Self {
+        Grams(value)
+    }
+}
+
+impl TryFrom<Grams> for u64 {
+    type Error = TonError;
+
+    fn try_from(value: Grams) -> Result<Self, Self::Error> {
+        u64::try_from(value.0)
+            .map_err(|_| TonErrorKind::InvalidData(format!("Grams amount exceeds u64: {}", value)).into())
+    }
+}
+
+impl std::str::FromStr for Grams {
+    type Err = TonError;
+
+    /// Parses nanograms amount from decimal or `0x` prefixed hex string. Sign is not allowed
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let string = string.trim();
+        let (digits, radix) = match string.strip_prefix("0x") {
+            Some(hex) => (hex, 16),
+            None => (string, 10)
+        };
+        if digits.starts_with('+') {
+            return Err(TonErrorKind::InvalidData(format!("Error parsing grams: {} (sign is not allowed)", string)).into());
+        }
+        u128::from_str_radix(digits, radix)
+            .map(Grams)
+            .map_err(|err|
+                TonErrorKind::InvalidData(format!("Error parsing grams: {} ({})", string, err)).into())
+    }
+}
+
+impl std::fmt::Display for Grams {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for Grams {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error> where
+        S: Serializer {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+struct GramsVisitor;
+
+impl<'de> Visitor<'de> for GramsVisitor {
+    type Value = Grams;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("nanograms amount as number, decimal or 0x prefixed hex string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> where E: serde::de::Error {
+        v.parse::<Grams>()
+            .map_err(|err| serde::de::Error::custom(format!("error decode grams: {}", err)))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> where E: serde::de::Error {
+        Ok(Grams::from(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> where E: serde::de::Error {
+        u64::try_from(v)
+            .map(Grams::from)
+            .map_err(|_| serde::de::Error::custom(format!("negative grams amount: {}", v)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Grams {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(GramsVisitor)
+    }
+}
@@ -0,0 +1,557 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Visitor;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use crate::abi_input::parse_integer;
+use crate::error::*;
+
+/// Unsigned 256-bit integer used for ABI `uint256` values (public keys, ids, hashes).
+///
+/// Serialized as `0x` prefixed 64 digits hex string like core returns `uint256` outputs.
+/// Deserialized from decimal or `0x` prefixed hex string or from JSON number.
+///
+/// Arithmetic operators panic on overflow and division by zero. Use `checked_*` methods when
+/// operands are not known to be in range.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct U256([u64; 4]);     // little-endian 64-bit limbs
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub fn from_be_bytes(bytes: [u8; 32]) -> U256 {
+        let mut limbs = [0u64; 4];
+        for (index, chunk) in bytes.chunks(8).enumerate() {
+            limbs[3 - index] = u64::from_be_bytes(<[u8; 8]>::try_from(chunk).unwrap());
+        }
+        U256(limbs)
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (index, limb) in self.0.iter().rev().enumerate() {
+            bytes[index * 8..index * 8 + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    // `None` if value exceeds 256 bits
+    pub(crate) fn from_be_slice(bytes: &[u8]) -> Option<U256> {
+        let significant = &bytes[bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len())..];
+        if significant.len() > 32 {
+            return None;
+        }
+        let mut padded = [0u8; 32];
+        padded[32 - significant.len()..].copy_from_slice(significant);
+        Some(U256::from_be_bytes(padded))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == U256::ZERO
+    }
+
+    /// Number of significant bits
+    pub fn bits(&self) -> u32 {
+        match self.0.iter().rposition(|limb| *limb != 0) {
+            Some(index) => index as u32 * 64 + 64 - self.0[index].leading_zeros(),
+            None => 0
+        }
+    }
+
+    fn bit(&self, index: u32) -> bool {
+        self.0[index as usize / 64] >> (index % 64) & 1 == 1
+    }
+
+    fn overflowing_add(self, other: U256) -> (U256, bool) {
+        let mut result = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (sum, overflow1) = self.0[i].overflowing_add(other.0[i]);
+            let (sum, overflow2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = overflow1 || overflow2;
+        }
+        (U256(result), carry)
+    }
+
+    fn overflowing_sub(self, other: U256) -> (U256, bool) {
+        let mut result = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (diff, overflow1) = self.0[i].overflowing_sub(other.0[i]);
+            let (diff, overflow2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = overflow1 || overflow2;
+        }
+        (U256(result), borrow)
+    }
+
+    fn shl1(self) -> U256 {
+        let mut result = [0u64; 4];
+        for (i, limb) in result.iter_mut().enumerate() {
+            *limb = self.0[i] << 1 | if i > 0 { self.0[i - 1] >> 63 } else { 0 };
+        }
+        U256(result)
+    }
+
+    // quotient and remainder of long division
+    fn div_rem(self, divider: U256) -> (U256, U256) {
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for index in (0..self.bits()).rev() {
+            remainder = remainder.shl1();
+            remainder.0[0] |= self.bit(index) as u64;
+            if remainder >= divider {
+                remainder = remainder.overflowing_sub(divider).0;
+                quotient.0[index as usize / 64] |= 1 << (index % 64);
+            }
+        }
+        (quotient, remainder)
+    }
+
+    pub fn checked_add(self, other: U256) -> Option<U256> {
+        match self.overflowing_add(other) {
+            (result, false) => Some(result),
+            _ => None
+        }
+    }
+
+    pub fn checked_sub(self, other: U256) -> Option<U256> {
+        match self.overflowing_sub(other) {
+            (result, false) => Some(result),
+            _ => None
+        }
+    }
+
+    pub fn checked_mul(self, other: U256) -> Option<U256> {
+        let mut result = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let product = self.0[i] as u128 * other.0[j] as u128 + result[i + j] as u128 + carry;
+                result[i + j] = product as u64;
+                carry = product >> 64;
+            }
+            result[i + 4] = carry as u64;
+        }
+        if result[4..].iter().any(|limb| *limb != 0) {
+            return None;
+        }
+        Some(U256([result[0], result[1], result[2], result[3]]))
+    }
+
+    pub fn checked_div(self, divider: U256) -> Option<U256> {
+        if divider.is_zero() {
+            return None;
+        }
+        Some(self.div_rem(divider).0)
+    }
+
+    pub fn checked_rem(self, divider: U256) -> Option<U256> {
+        if divider.is_zero() {
+            return None;
+        }
+        Some(self.div_rem(divider).1)
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(value: u64) -> Self {
+        U256([value, 0, 0, 0])
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(value: u128) -> Self {
+        U256([value as u64, (value >> 64) as u64, 0, 0])
+    }
+}
+
+impl TryFrom<U256> for u64 {
+    type Error = TonError;
+
+    fn try_from(value: U256) -> Result<Self, Self::Error> {
+        if value.bits() > 64 {
+            return Err(TonErrorKind::InvalidData(format!("Integer exceeds u64: {}", value)).into());
+        }
+        Ok(value.0[0])
+    }
+}
+
+impl TryFrom<U256> for u128 {
+    type Error = TonError;
+
+    fn try_from(value: U256) -> Result<Self, Self::Error> {
+        if value.bits() > 128 {
+            return Err(TonErrorKind::InvalidData(format!("Integer exceeds u128: {}", value)).into());
+        }
+        Ok((value.0[1] as u128) << 64 | value.0[0] as u128)
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// operators panic like primitive integers do in debug builds
+macro_rules! impl_ops {
+    ($type:ty, $($trait:ident, $method:ident, $checked:ident, $msg:expr;)*) => {
+        $(
+            impl std::ops::$trait for $type {
+                type Output = $type;
+
+                fn $method(self, other: $type) -> $type {
+                    self.$checked(other).expect($msg)
+                }
+            }
+        )*
+    };
+}
+
+impl_ops!(U256,
+    Add, add, checked_add, "attempt to add with overflow";
+    Sub, sub, checked_sub, "attempt to subtract with overflow";
+    Mul, mul, checked_mul, "attempt to multiply with overflow";
+    Div, div, checked_div, "attempt to divide by zero";
+    Rem, rem, checked_rem, "attempt to calculate the remainder with a divisor of zero";
+);
+
+impl std::str::FromStr for U256 {
+    type Err = TonError;
+
+    /// Parses integer from decimal or `0x` prefixed hex string
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let string = string.trim();
+        match parse_integer(string) {
+            Some((false, magnitude)) => U256::from_be_slice(&magnitude),
+            _ => None
+        }.ok_or_else(|| TonErrorKind::InvalidData(format!("Error parsing uint256: {}", string)).into())
+    }
+}
+
+impl std::fmt::Display for U256 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        // split into 19 digits chunks fitting into u64
+        let chunk_divider = U256::from(10_000_000_000_000_000_000u64);
+        let mut chunks = vec![];
+        let mut value = *self;
+        loop {
+            let (quotient, remainder) = value.div_rem(chunk_divider);
+            chunks.push(remainder.0[0]);
+            if quotient.is_zero() {
+                break;
+            }
+            value = quotient;
+        }
+        let mut string = chunks.pop().unwrap_or_default().to_string();
+        for chunk in chunks.iter().rev() {
+            string += &format!("{:019}", chunk);
+        }
+        f.pad_integral(true, "", &string)
+    }
+}
+
+impl std::fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let string = hex::encode(self.to_be_bytes());
+        let digits = string.trim_start_matches('0');
+        f.pad_integral(true, "0x", if digits.is_empty() { "0" } else { digits })
+    }
+}
+
+impl std::fmt::Debug for U256 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "U256({})", self)
+    }
+}
+
+impl Serialize for U256 {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error> where
+        S: Serializer {
+        serializer.serialize_str(&format!("0x{}", hex::encode(self.to_be_bytes())))
+    }
+}
+
+struct U256Visitor;
+
+impl<'de> Visitor<'de> for U256Visitor {
+    type Value = U256;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("unsigned integer as number, decimal or 0x prefixed hex string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> where E: serde::de::Error {
+        v.parse::<U256>()
+            .map_err(|err| serde::de::Error::custom(format!("error decode uint256: {}", err)))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> where E: serde::de::Error {
+        Ok(U256::from(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> where E: serde::de::Error {
+        u64::try_from(v)
+            .map(U256::from)
+            .map_err(|_| serde::de::Error::custom(format!("negative uint256 value: {}", v)))
+    }
+}
+
+impl<'de> Deserialize<'de> for U256 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(U256Visitor)
+    }
+}
+
+/// Signed 256-bit integer used for ABI `int256` values.
+///
+/// Serialized as decimal string. Deserialized from decimal or `0x` prefixed hex string with
+/// optional `-` sign or from JSON number.
+///
+/// Arithmetic operators panic on overflow and division by zero. Use `checked_*` methods when
+/// operands are not known to be in range.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct I256(U256);     // two's complement
+
+impl I256 {
+    pub const ZERO: I256 = I256(U256::ZERO);
+    pub const ONE: I256 = I256(U256::ONE);
+    pub const MIN: I256 = I256(U256([0, 0, 0, 1 << 63]));
+    pub const MAX: I256 = I256(U256([u64::MAX, u64::MAX, u64::MAX, u64::MAX >> 1]));
+
+    // `None` if value is out of range
+    pub(crate) fn from_sign_magnitude(negative: bool, magnitude: U256) -> Option<I256> {
+        if negative {
+            if magnitude > I256::MIN.0 {
+                return None;
+            }
+            Some(I256(U256::ZERO.overflowing_sub(magnitude).0))
+        } else if magnitude > I256::MAX.0 {
+            None
+        } else {
+            Some(I256(magnitude))
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0.bit(255)
+    }
+
+    pub fn unsigned_abs(&self) -> U256 {
+        if self.is_negative() {
+            U256::ZERO.overflowing_sub(self.0).0
+        } else {
+            self.0
+        }
+    }
+
+    pub fn checked_neg(self) -> Option<I256> {
+        I256::from_sign_magnitude(!self.is_negative(), self.unsigned_abs())
+    }
+
+    pub fn checked_add(self, other: I256) -> Option<I256> {
+        let result = I256(self.0.overflowing_add(other.0).0);
+        // overflow if operands of the same sign give result of another sign
+        if self.is_negative() == other.is_negative() && result.is_negative() != self.is_negative() {
+            return None;
+        }
+        Some(result)
+    }
+
+    pub fn checked_sub(self, other: I256) -> Option<I256> {
+        let result = I256(self.0.overflowing_sub(other.0).0);
+        if self.is_negative() != other.is_negative() && result.is_negative() != self.is_negative() {
+            return None;
+        }
+        Some(result)
+    }
+
+    pub fn checked_mul(self, other: I256) -> Option<I256> {
+        let magnitude = self.unsigned_abs().checked_mul(other.unsigned_abs())?;
+        I256::from_sign_magnitude(self.is_negative() != other.is_negative(), magnitude)
+    }
+
+    /// Division rounding towards zero
+    pub fn checked_div(self, divider: I256) -> Option<I256> {
+        let magnitude = self.unsigned_abs().checked_div(divider.unsigned_abs())?;
+        I256::from_sign_magnitude(self.is_negative() != divider.is_negative(), magnitude)
+    }
+
+    /// Remainder having the sign of dividend
+    pub fn checked_rem(self, divider: I256) -> Option<I256> {
+        let magnitude = self.unsigned_abs().checked_rem(divider.unsigned_abs())?;
+        I256::from_sign_magnitude(self.is_negative(), magnitude)
+    }
+}
+
+impl From<i64> for I256 {
+    fn from(value: i64) -> Self {
+        I256::from(value as i128)
+    }
+}
+
+impl From<i128> for I256 {
+    fn from(value: i128) -> Self {
+        // sign extension
+        let high = if value < 0 { u64::MAX } else { 0 };
+        I256(U256([value as u64, (value >> 64) as u64, high, high]))
+    }
+}
+
+impl From<u64> for I256 {
+    fn from(value: u64) -> Self {
+        I256(U256::from(value))
+    }
+}
+
+impl TryFrom<U256> for I256 {
+    type Error = TonError;
+
+    fn try_from(value: U256) -> Result<Self, Self::Error> {
+        I256::from_sign_magnitude(false, value)
+            .ok_or_else(|| TonErrorKind::InvalidData(format!("Integer exceeds int256: {}", value)).into())
+    }
+}
+
+impl TryFrom<I256> for U256 {
+    type Error = TonError;
+
+    fn try_from(value: I256) -> Result<Self, Self::Error> {
+        if value.is_negative() {
+            return Err(TonErrorKind::InvalidData(format!("Negative uint256 value: {}", value)).into());
+        }
+        Ok(value.0)
+    }
+}
+
+impl TryFrom<I256> for i64 {
+    type Error = TonError;
+
+    fn try_from(value: I256) -> Result<Self, Self::Error> {
+        i128::try_from(value)
+            .and_then(|value| i64::try_from(value)
+                .map_err(|_| TonErrorKind::InvalidData(format!("Integer exceeds i64: {}", value)).into()))
+    }
+}
+
+impl TryFrom<I256> for i128 {
+    type Error = TonError;
+
+    fn try_from(value: I256) -> Result<Self, Self::Error> {
+        let magnitude = value.unsigned_abs();
+        let fits = magnitude.bits() < 128 || value.is_negative() && magnitude == U256::from(1u128 << 127);
+        if !fits {
+            return Err(TonErrorKind::InvalidData(format!("Integer exceeds i128: {}", value)).into());
+        }
+        Ok((value.0 .0[1] as i128) << 64 | value.0 .0[0] as i128)
+    }
+}
+
+impl Ord for I256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.is_negative().cmp(&self.is_negative())
+            .then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for I256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl_ops!(I256,
+    Add, add, checked_add, "attempt to add with overflow";
+    Sub, sub, checked_sub, "attempt to subtract with overflow";
+    Mul, mul, checked_mul, "attempt to multiply with overflow";
+    Div, div, checked_div, "attempt to divide by zero or with overflow";
+    Rem, rem, checked_rem, "attempt to calculate the remainder with a divisor of zero";
+);
+
+impl std::ops::Neg for I256 {
+    type Output = I256;
+
+    fn neg(self) -> I256 {
+        self.checked_neg().expect("attempt to negate with overflow")
+    }
+}
+
+impl std::str::FromStr for I256 {
+    type Err = TonError;
+
+    /// Parses integer from decimal or `0x` prefixed hex string with optional `-` sign
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let string = string.trim();
+        parse_integer(string)
+            .and_then(|(negative, magnitude)| I256::from_sign_magnitude(
+                negative, U256::from_be_slice(&magnitude)?))
+            .ok_or_else(|| TonErrorKind::InvalidData(format!("Error parsing int256: {}", string)).into())
+    }
+}
+
+impl std::fmt::Display for I256 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.pad_integral(!self.is_negative(), "", &self.unsigned_abs().to_string())
+    }
+}
+
+impl std::fmt::Debug for I256 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "I256({})", self)
+    }
+}
+
+impl Serialize for I256 {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error> where
+        S: Serializer {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+struct I256Visitor;
+
+impl<'de> Visitor<'de> for I256Visitor {
+    type Value = I256;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("integer as number, decimal or 0x prefixed hex string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E> where E: serde::de::Error {
+        v.parse::<I256>()
+            .map_err(|err| serde::de::Error::custom(format!("error decode int256: {}", err)))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> where E: serde::de::Error {
+        Ok(I256::from(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> where E: serde::de::Error {
+        Ok(I256::from(v))
+    }
+}
+
+impl<'de> Deserialize<'de> for I256 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(I256Visitor)
+    }
+}
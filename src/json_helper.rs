@@ -2,7 +2,7 @@
  * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
  */
 
- use crate::contracts::{EncodedMessage, ResultOfGetDeployData, TransactionFees};
+ use crate::contracts::{EncodedMessage, ResultOfGetDeployData, TransactionFees, UnsignedMessage};
  use crate::TonAddress;
  use crate::error::*;
  use std::convert::TryFrom;
@@ -42,13 +42,13 @@ pub(crate) struct EncodedMessageCore {
     pub address: TonAddress,
 }
 
-impl Into<EncodedMessageCore> for EncodedMessage {
-    fn into(self) -> EncodedMessageCore {
+impl From<EncodedMessage> for EncodedMessageCore {
+    fn from(value: EncodedMessage) -> Self {
         EncodedMessageCore {
-            message_id: self.message_id,
-            message_body_base64: base64::encode(&self.message_body),
-            expire: self.expire,
-            address: self.address
+            message_id: value.message_id,
+            message_body_base64: base64::encode(&value.message_body),
+            expire: value.expire,
+            address: value.address
         }
     }
 }
@@ -66,6 +66,62 @@ impl TryFrom<EncodedMessageCore> for EncodedMessage {
     }
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EncodedUnsignedMessageCore {
+    pub unsigned_bytes_base64: String,
+    pub bytes_to_sign_base64: String,
+    pub expire: Option<u32>,
+}
+
+impl EncodedUnsignedMessageCore {
+    pub fn into_unsigned_message(self, address: TonAddress, abi: serde_json::Value) -> TonResult<UnsignedMessage> {
+        Ok(UnsignedMessage {
+            unsigned_bytes: base64::decode(&self.unsigned_bytes_base64)?,
+            bytes_to_sign: base64::decode(&self.bytes_to_sign_base64)?,
+            expire: self.expire,
+            address,
+            abi,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UnsignedMessageCore {
+    pub unsigned_bytes_base64: String,
+    pub bytes_to_sign_base64: String,
+    pub expire: Option<u32>,
+    pub address: TonAddress,
+    pub abi: serde_json::Value,
+}
+
+impl From<UnsignedMessage> for UnsignedMessageCore {
+    fn from(value: UnsignedMessage) -> Self {
+        UnsignedMessageCore {
+            unsigned_bytes_base64: base64::encode(&value.unsigned_bytes),
+            bytes_to_sign_base64: base64::encode(&value.bytes_to_sign),
+            expire: value.expire,
+            address: value.address,
+            abi: value.abi,
+        }
+    }
+}
+
+impl TryFrom<UnsignedMessageCore> for UnsignedMessage {
+    type Error = TonError;
+
+    fn try_from(value: UnsignedMessageCore) -> Result<Self, Self::Error> {
+        Ok(UnsignedMessage {
+            unsigned_bytes: base64::decode(&value.unsigned_bytes_base64)?,
+            bytes_to_sign: base64::decode(&value.bytes_to_sign_base64)?,
+            expire: value.expire,
+            address: value.address,
+            abi: value.abi,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ResultOfGetDeployDataCore {
@@ -87,10 +143,10 @@ impl TryFrom<ResultOfGetDeployDataCore> for ResultOfGetDeployData {
 }
 
 fn u64_from_str(string: String) -> TonResult<u64> {
-    if string.starts_with("0x") {
-        u64::from_str_radix(&string[2..], 16)
+    if let Some(hex) = string.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16)
     } else {
-        u64::from_str_radix(&string, 10)
+        string.parse::<u64>()
     }.map_err(|err| 
         TonErrorKind::InvalidData(format!("Error parsing number: {} ({})", string, err)).into())
 }
@@ -40,4 +40,65 @@ pub use queries::*;
 mod error;
 pub use error::*;
 
+mod grams;
+pub use grams::*;
+
+mod int256;
+pub use int256::*;
+
+mod cell;
+pub use cell::*;
+
+mod message_builder;
+pub use message_builder::*;
+
+mod envelope;
+pub use envelope::*;
+
+mod outbox;
+pub use outbox::*;
+
+mod retry;
+pub use retry::*;
+
+mod processing;
+pub use processing::*;
+
+mod giver;
+pub use giver::*;
+
+mod multisig;
+pub use multisig::*;
+
+mod transfer;
+pub use transfer::*;
+
+mod tvm;
+pub use tvm::*;
+
+mod snapshot;
+pub use snapshot::*;
+
+mod clock;
+pub use clock::*;
+
+mod sandbox;
+pub use sandbox::*;
+
+mod events;
+pub use events::*;
+
+mod message_decoder;
+pub use message_decoder::*;
+
+mod abi;
+pub use abi::*;
+
+mod abi_input;
+
+pub mod abi_serde;
+
+mod abi_registry;
+pub use abi_registry::*;
+
 mod json_helper;
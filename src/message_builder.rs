@@ -0,0 +1,173 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{Cell, CellBuilder, EncodedMessage, Ed25519KeyPair, Grams, JsonValue, TonAddress, TonContracts};
+use crate::error::*;
+
+#[derive(Clone, Debug, PartialEq)]
+enum MessageHeader {
+    Internal {
+        src: Option<TonAddress>,
+        value: Grams,
+        bounce: bool,
+        created_lt: u64,
+        created_at: u32,
+    },
+    ExternalInbound,
+}
+
+/// Builder for internal and external inbound messages with arbitrary body and state init.
+///
+/// Built message can be processed locally with `run_local_msg` to emulate contract-to-contract
+/// calls or sent to the node (external messages only).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageBuilder {
+    header: MessageHeader,
+    dst: TonAddress,
+    state_init: Option<Cell>,
+    body: Option<Cell>,
+}
+
+impl MessageBuilder {
+    /// Internal message carrying `value` nanograms. Bounce flag is set by default
+    pub fn internal(src: Option<TonAddress>, dst: TonAddress, value: Grams) -> MessageBuilder {
+        MessageBuilder {
+            header: MessageHeader::Internal {
+                src,
+                value,
+                bounce: true,
+                created_lt: 0,
+                created_at: 0,
+            },
+            dst,
+            state_init: None,
+            body: None,
+        }
+    }
+
+    /// External inbound message
+    pub fn external(dst: TonAddress) -> MessageBuilder {
+        MessageBuilder {
+            header: MessageHeader::ExternalInbound,
+            dst,
+            state_init: None,
+            body: None,
+        }
+    }
+
+    /// Set bounce flag of internal message. Ignored for external messages
+    pub fn bounce(mut self, bounce: bool) -> Self {
+        if let MessageHeader::Internal { bounce: ref mut flag, .. } = self.header {
+            *flag = bounce;
+        }
+        self
+    }
+
+    /// Set logical creation time and unix creation time of internal message. Ignored for external
+    /// messages
+    pub fn created(mut self, lt: u64, time: u32) -> Self {
+        if let MessageHeader::Internal { ref mut created_lt, ref mut created_at, .. } = self.header {
+            *created_lt = lt;
+            *created_at = time;
+        }
+        self
+    }
+
+    /// Attach state init from contract image. Use image returned by `get_deploy_data` to
+    /// deploy contract with public key and initial data set
+    pub fn state_init(mut self, image: &[u8]) -> TonResult<Self> {
+        self.state_init = Some(Cell::from_boc(image)?);
+        Ok(self)
+    }
+
+    /// Set raw message body
+    pub fn body(mut self, body: Cell) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    /// Set message body encoded from contract function call
+    pub fn body_from_abi(
+        mut self,
+        contracts: &TonContracts,
+        abi: JsonValue,
+        function_name: &str,
+        header: Option<JsonValue>,
+        input: JsonValue,
+        keys: Option<&Ed25519KeyPair>,
+    ) -> TonResult<Self> {
+        let internal = self.is_internal();
+        let body = contracts.encode_message_body(abi, function_name, header, input, internal, keys)?;
+        self.body = Some(Cell::from_boc(&body)?);
+        Ok(self)
+    }
+
+    pub fn is_internal(&self) -> bool {
+        match self.header {
+            MessageHeader::Internal { .. } => true,
+            MessageHeader::ExternalInbound => false,
+        }
+    }
+
+    /// Build message root cell
+    pub fn build_cell(&self) -> TonResult<Cell> {
+        let mut builder = CellBuilder::new();
+        match &self.header {
+            MessageHeader::Internal { src, value, bounce, created_lt, created_at } => {
+                builder.append_bit(false)?;             // int_msg_info$0
+                builder.append_bit(true)?;              // ihr_disabled
+                builder.append_bit(*bounce)?;
+                builder.append_bit(false)?;             // bounced
+                builder.append_address(src.as_ref())?;
+                builder.append_address(Some(&self.dst))?;
+                builder.append_grams(*value)?;
+                builder.append_bit(false)?;             // no extra currencies
+                builder.append_grams(Grams::zero())?;   // ihr_fee
+                builder.append_grams(Grams::zero())?;   // fwd_fee
+                builder.append_u64(*created_lt)?;
+                builder.append_u32(*created_at)?;
+            }
+            MessageHeader::ExternalInbound => {
+                builder.append_bits(0b10, 2)?;          // ext_in_msg_info$10
+                builder.append_address(None)?;
+                builder.append_address(Some(&self.dst))?;
+                builder.append_grams(Grams::zero())?;   // import_fee
+            }
+        }
+
+        match &self.state_init {
+            Some(state_init) => {
+                builder.append_bits(0b11, 2)?;          // state init is stored in reference
+                builder.append_reference(state_init.clone())?;
+            }
+            None => { builder.append_bit(false)?; }
+        }
+
+        match &self.body {
+            Some(body) => {
+                builder.append_bit(true)?;              // body is stored in reference
+                builder.append_reference(body.clone())?;
+            }
+            None => { builder.append_bit(false)?; }
+        }
+
+        builder.build()
+    }
+
+    /// Build message serialized into bag of cells
+    pub fn build_boc(&self) -> TonResult<Vec<u8>> {
+        self.build_cell()?.to_boc()
+    }
+
+    /// Build message ready for `run_local_msg` or `send_message`
+    pub fn build(&self, contracts: &TonContracts) -> TonResult<EncodedMessage> {
+        let message_body = self.build_boc()?;
+        Ok(EncodedMessage {
+            message_id: contracts.get_boc_hash(&message_body)?,
+            message_body,
+            expire: None,
+            address: self.dst.clone(),
+        })
+    }
+}
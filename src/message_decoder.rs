@@ -0,0 +1,243 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{Cell, CellBuilder, CellSlice, Grams, JsonValue, TonAddress, TonContracts};
+use crate::error::*;
+use crate::events::abi_event_names;
+use serde_json::Value;
+
+/// Message type determined from message header
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MessageType {
+    Internal,
+    ExternalIn,
+    ExternalOut,
+}
+
+/// Message header fields, state init and body parsed from message BOC
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsedMessage {
+    pub message_type: MessageType,
+    /// Source address. `None` for external inbound messages
+    pub src: Option<TonAddress>,
+    /// Destination address. `None` for external outbound messages
+    pub dst: Option<TonAddress>,
+    /// Transferred value. `None` for external messages
+    pub value: Option<Grams>,
+    pub bounce: bool,
+    pub bounced: bool,
+    pub created_lt: u64,
+    pub created_at: u32,
+    pub state_init: Option<Cell>,
+    pub body: Option<Cell>,
+}
+
+/// Kind of encoded message body
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MessageBodyType {
+    /// Contract function call
+    Input,
+    /// Contract function response
+    Output,
+    Event,
+    /// Body not matching ABI: plain transfer, text comment or bounced message. Raw body is
+    /// available in `ParsedMessage::body`
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedMessageBody {
+    pub body_type: MessageBodyType,
+    /// Function or event name. Empty for `Unknown` body
+    pub name: String,
+    /// Decoded parameters. `null` for `Unknown` body
+    pub params: Value,
+}
+
+impl DecodedMessageBody {
+    fn unknown() -> Self {
+        DecodedMessageBody {
+            body_type: MessageBodyType::Unknown,
+            name: String::new(),
+            params: Value::Null,
+        }
+    }
+}
+
+/// Result of `decode_message` function
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedMessage {
+    pub message: ParsedMessage,
+    /// `None` if message has no body
+    pub body: Option<DecodedMessageBody>,
+}
+
+/// Parse message header, state init and body from message BOC
+pub fn parse_message(boc: &[u8]) -> TonResult<ParsedMessage> {
+    let mut slice = Cell::from_boc(boc)?.slice();
+
+    let mut message = if !slice.get_bit()? {
+        // int_msg_info$0
+        slice.get_bit()?;                           // ihr_disabled
+        let bounce = slice.get_bit()?;
+        let bounced = slice.get_bit()?;
+        let src = slice.get_address()?;
+        let dst = slice.get_address()?;
+        let value = slice.get_grams()?;
+        if slice.get_bit()? {                       // extra currencies dictionary
+            slice.get_reference()?;
+        }
+        slice.get_grams()?;                         // ihr_fee
+        slice.get_grams()?;                         // fwd_fee
+        ParsedMessage {
+            message_type: MessageType::Internal,
+            src,
+            dst,
+            value: Some(value),
+            bounce,
+            bounced,
+            created_lt: slice.get_u64()?,
+            created_at: slice.get_u32()?,
+            state_init: None,
+            body: None,
+        }
+    } else if !slice.get_bit()? {
+        // ext_in_msg_info$10
+        slice.get_address()?;
+        let dst = slice.get_address()?;
+        slice.get_grams()?;                         // import_fee
+        ParsedMessage {
+            message_type: MessageType::ExternalIn,
+            src: None,
+            dst,
+            value: None,
+            bounce: false,
+            bounced: false,
+            created_lt: 0,
+            created_at: 0,
+            state_init: None,
+            body: None,
+        }
+    } else {
+        // ext_out_msg_info$11
+        let src = slice.get_address()?;
+        slice.get_address()?;
+        ParsedMessage {
+            message_type: MessageType::ExternalOut,
+            src,
+            dst: None,
+            value: None,
+            bounce: false,
+            bounced: false,
+            created_lt: slice.get_u64()?,
+            created_at: slice.get_u32()?,
+            state_init: None,
+            body: None,
+        }
+    };
+
+    if slice.get_bit()? {
+        message.state_init = Some(if slice.get_bit()? {
+            slice.get_reference()?
+        } else {
+            read_inline_state_init(&mut slice)?
+        });
+    }
+
+    message.body = if slice.get_bit()? {
+        Some(slice.get_reference()?)
+    } else if slice.is_empty() {
+        None
+    } else {
+        Some(slice.into_cell()?)
+    };
+
+    Ok(message)
+}
+
+fn read_inline_state_init(slice: &mut CellSlice) -> TonResult<Cell> {
+    let mut builder = CellBuilder::new();
+    // split_depth:(Maybe (## 5)) special:(Maybe TickTock)
+    for bits in &[5, 2] {
+        let present = slice.get_bit()?;
+        builder.append_bit(present)?;
+        if present {
+            builder.append_bits(slice.get_bits(*bits)?, *bits)?;
+        }
+    }
+    // code:(Maybe ^Cell) data:(Maybe ^Cell) library:(HashmapE 256 SimpleLib)
+    for _ in 0..3 {
+        let present = slice.get_bit()?;
+        builder.append_bit(present)?;
+        if present {
+            builder.append_reference(slice.get_reference()?)?;
+        }
+    }
+    builder.build()
+}
+
+impl TonContracts {
+    /// Parse message and decode its body with given ABI. Internal and external inbound message
+    /// bodies are decoded as function calls, external outbound message bodies are decoded as
+    /// events or function responses. Internal message body with function id having the highest
+    /// bit set is decoded as function response. Bounced messages and bodies not matching ABI are
+    /// returned with `Unknown` body type
+    pub fn decode_message(&self, abi: JsonValue, message_boc: &[u8]) -> TonResult<DecodedMessage> {
+        let message = parse_message(message_boc)?;
+        let abi = abi.to_value()?;
+        let body = match &message.body {
+            Some(body) if !message.bounced => Some(
+                self.decode_message_body(abi.into(), message.message_type, body)
+                    .unwrap_or_else(|_| DecodedMessageBody::unknown())),
+            Some(_) => Some(DecodedMessageBody::unknown()),
+            None => None
+        };
+        Ok(DecodedMessage { message, body })
+    }
+
+    fn decode_message_body(
+        &self,
+        abi: JsonValue,
+        message_type: MessageType,
+        body: &Cell,
+    ) -> TonResult<DecodedMessageBody> {
+        let body_boc = body.to_boc()?;
+        // response to internal call has function id with the highest bit set
+        let is_response = message_type == MessageType::Internal
+            && body.slice().get_u32().map(|id| id & 0x8000_0000 != 0).unwrap_or(false);
+        match message_type {
+            MessageType::Internal if is_response => {
+                let decoded = self.decode_output_message_body(abi, &body_boc)?;
+                Ok(DecodedMessageBody {
+                    body_type: MessageBodyType::Output,
+                    name: decoded.function,
+                    params: decoded.output,
+                })
+            }
+            MessageType::Internal | MessageType::ExternalIn => {
+                let internal = message_type == MessageType::Internal;
+                let decoded = self.decode_input_message_body(abi, &body_boc, internal)?;
+                Ok(DecodedMessageBody {
+                    body_type: MessageBodyType::Input,
+                    name: decoded.function,
+                    params: decoded.output,
+                })
+            }
+            MessageType::ExternalOut => {
+                let event_names = abi_event_names(&abi.clone().to_value()?);
+                let decoded = self.decode_output_message_body(abi, &body_boc)?;
+                let body_type = if event_names.contains(&decoded.function) {
+                    MessageBodyType::Event
+                } else {
+                    MessageBodyType::Output
+                };
+                Ok(DecodedMessageBody {
+                    body_type,
+                    name: decoded.function,
+                    params: decoded.output,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{Ed25519KeyPair, Grams, JsonValue, ResultOfRun, TonAddress, TonContracts, Transfer, U256};
+use crate::abi_input::parse_integer;
+use crate::error::*;
+use serde_json::Value;
+use std::convert::TryFrom;
+
+/// ABI of SafeMultisigWallet contract
+pub const SAFE_MULTISIG_ABI: &str = include_str!("contracts/SafeMultisigWallet.abi.json");
+
+/// ABI of SetcodeMultisigWallet contract. It extends SafeMultisigWallet ABI with code update
+/// functions, so it can be used with `MultisigWallet::with_abi`
+pub const SETCODE_MULTISIG_ABI: &str = include_str!("contracts/SetcodeMultisigWallet.abi.json");
+
+/// Pending multisig transaction waiting for custodians confirmations
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultisigTransaction {
+    pub id: u64,
+    /// Bit mask of custodians indexes confirmed transaction
+    pub confirmations_mask: u32,
+    pub signs_required: u8,
+    pub signs_received: u8,
+    /// Public key of custodian submitted transaction (hex encoded)
+    pub creator: String,
+    /// Index of custodian submitted transaction
+    pub index: u8,
+    pub dest: TonAddress,
+    pub value: Grams,
+    pub send_flags: u16,
+    /// Base64 encoded payload BOC
+    pub payload: String,
+    pub bounce: bool,
+}
+
+impl MultisigTransaction {
+    /// Number of confirmations needed to execute transaction
+    pub fn confirmations_left(&self) -> u8 {
+        self.signs_required.saturating_sub(self.signs_received)
+    }
+
+    /// Check if custodian with given index confirmed transaction
+    pub fn is_confirmed_by(&self, custodian_index: u8) -> bool {
+        custodian_index < 32 && self.confirmations_mask & (1 << custodian_index) != 0
+    }
+
+    pub(crate) fn from_json(value: &Value) -> TonResult<Self> {
+        Ok(MultisigTransaction {
+            id: get_uint(value, "id")?,
+            confirmations_mask: get_uint(value, "confirmationsMask")?,
+            signs_required: get_uint(value, "signsRequired")?,
+            signs_received: get_uint(value, "signsReceived")?,
+            creator: get_uint256(value, "creator")?,
+            index: get_uint(value, "index")?,
+            dest: TonAddress::from_str(get_str(value, "dest")?)?,
+            value: Grams(get_uint(value, "value")?),
+            send_flags: get_uint(value, "sendFlags")?,
+            payload: get_str(value, "payload")?.to_owned(),
+            bounce: value["bounce"].as_bool()
+                .ok_or_else(|| invalid_field("bounce"))?,
+        })
+    }
+}
+
+/// Multisig wallet custodian
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultisigCustodian {
+    pub index: u8,
+    /// Custodian public key (hex encoded)
+    pub pubkey: String,
+}
+
+/// Multisig wallet parameters returned by `getParameters`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultisigParameters {
+    pub max_queued_transactions: u8,
+    pub max_custodian_count: u8,
+    /// Pending transaction lifetime in seconds
+    pub expiration_time: u64,
+    pub min_value: Grams,
+    pub required_txn_confirms: u8,
+}
+
+/// Result of `submit_transaction`
+#[derive(Debug, PartialEq)]
+pub struct ResultOfSubmitTransaction {
+    /// Id of created pending transaction. 0 if transfer was executed immediately because
+    /// wallet requires single confirmation
+    pub transaction_id: u64,
+    pub result: ResultOfRun,
+}
+
+/// SafeMultisig/SetcodeMultisig wallet helper.
+///
+/// Getters accept optional account BOC in the same way as `run_local`, so wallet state can be
+/// inspected without network
+#[derive(Clone, Debug)]
+pub struct MultisigWallet {
+    pub address: TonAddress,
+    abi: JsonValue,
+}
+
+impl MultisigWallet {
+    /// Wallet using bundled SafeMultisigWallet ABI
+    pub fn new(address: TonAddress) -> Self {
+        Self::with_abi(address, SAFE_MULTISIG_ABI.into())
+    }
+
+    /// Wallet using custom multisig ABI (e.g. `SETCODE_MULTISIG_ABI`)
+    pub fn with_abi(address: TonAddress, abi: JsonValue) -> Self {
+        MultisigWallet { address, abi }
+    }
+
+    pub fn abi(&self) -> &JsonValue {
+        &self.abi
+    }
+
+    /// Submit new transaction. Transaction is executed immediately if wallet requires single
+    /// confirmation, otherwise it waits for other custodians confirmations
+    pub fn submit_transaction(
+        &self,
+        contracts: &TonContracts,
+        keys: &Ed25519KeyPair,
+        transfer: &Transfer,
+    ) -> TonResult<ResultOfSubmitTransaction> {
+        let result = contracts.run(
+            &self.address,
+            self.abi.clone(),
+            "submitTransaction",
+            None,
+            submit_transaction_input(transfer)?.into(),
+            Some(keys))?;
+
+        Ok(ResultOfSubmitTransaction {
+            transaction_id: get_uint(&result.output, "transId")?,
+            result,
+        })
+    }
+
+    pub fn confirm_transaction(
+        &self,
+        contracts: &TonContracts,
+        keys: &Ed25519KeyPair,
+        transaction_id: u64,
+    ) -> TonResult<ResultOfRun> {
+        contracts.run(
+            &self.address,
+            self.abi.clone(),
+            "confirmTransaction",
+            None,
+            serde_json::json!({
+                "transactionId": transaction_id.to_string(),
+            }).into(),
+            Some(keys))
+    }
+
+    /// Pending transactions
+    pub fn get_transactions(
+        &self,
+        contracts: &TonContracts,
+        account: Option<JsonValue>,
+    ) -> TonResult<Vec<MultisigTransaction>> {
+        let output = self.run_getter(contracts, account, "getTransactions")?;
+        output["transactions"].as_array()
+            .ok_or_else(|| invalid_field("transactions"))?
+            .iter()
+            .map(MultisigTransaction::from_json)
+            .collect()
+    }
+
+    pub fn get_custodians(
+        &self,
+        contracts: &TonContracts,
+        account: Option<JsonValue>,
+    ) -> TonResult<Vec<MultisigCustodian>> {
+        let output = self.run_getter(contracts, account, "getCustodians")?;
+        parse_custodians(&output)
+    }
+
+    pub fn get_parameters(
+        &self,
+        contracts: &TonContracts,
+        account: Option<JsonValue>,
+    ) -> TonResult<MultisigParameters> {
+        let output = self.run_getter(contracts, account, "getParameters")?;
+        parse_parameters(&output)
+    }
+
+    /// Number of custodians confirmations required to execute transaction
+    pub fn required_confirmations(
+        &self,
+        contracts: &TonContracts,
+        account: Option<JsonValue>,
+    ) -> TonResult<u8> {
+        Ok(self.get_parameters(contracts, account)?.required_txn_confirms)
+    }
+
+    fn run_getter(
+        &self,
+        contracts: &TonContracts,
+        account: Option<JsonValue>,
+        function_name: &str,
+    ) -> TonResult<Value> {
+        Ok(contracts.run_local(
+            &self.address,
+            account,
+            self.abi.clone(),
+            function_name,
+            None,
+            serde_json::json!({}).into(),
+            None,
+            None,
+            false,
+        )?.output)
+    }
+}
+
+/// Input of `submitTransaction` function. Empty cell is sent if `payload` is not set
+pub(crate) fn submit_transaction_input(transfer: &Transfer) -> TonResult<Value> {
+    let payload = transfer.payload.clone().unwrap_or_default();
+    Ok(serde_json::json!({
+        "dest": transfer.dest.to_string(),
+        "value": transfer.amount,
+        "bounce": transfer.bounce,
+        "allBalance": transfer.all_balance,
+        "payload": base64::encode(&payload.to_boc()?),
+    }))
+}
+
+pub(crate) fn parse_custodians(output: &Value) -> TonResult<Vec<MultisigCustodian>> {
+    output["custodians"].as_array()
+        .ok_or_else(|| invalid_field("custodians"))?
+        .iter()
+        .map(|custodian| Ok(MultisigCustodian {
+            index: get_uint(custodian, "index")?,
+            pubkey: get_uint256(custodian, "pubkey")?,
+        }))
+        .collect()
+}
+
+pub(crate) fn parse_parameters(output: &Value) -> TonResult<MultisigParameters> {
+    Ok(MultisigParameters {
+        max_queued_transactions: get_uint(output, "maxQueuedTransactions")?,
+        max_custodian_count: get_uint(output, "maxCustodianCount")?,
+        expiration_time: get_uint(output, "expirationTime")?,
+        min_value: Grams(get_uint(output, "minValue")?),
+        required_txn_confirms: get_uint(output, "requiredTxnConfirms")?,
+    })
+}
+
+fn invalid_field(name: &str) -> TonError {
+    TonErrorKind::InvalidData(format!("Invalid multisig output field `{}`", name)).into()
+}
+
+fn get_str<'a>(value: &'a Value, name: &str) -> TonResult<&'a str> {
+    value[name].as_str().ok_or_else(|| invalid_field(name))
+}
+
+// integers are returned by core as decimal or hex strings
+fn get_uint<T: TryFrom<u128>>(value: &Value, name: &str) -> TonResult<T> {
+    let number = match &value[name] {
+        Value::Number(number) => number.as_u64().map(U256::from),
+        Value::String(string) => parse_integer(string)
+            .filter(|(negative, _)| !negative)
+            .and_then(|(_, magnitude)| U256::from_be_slice(&magnitude)),
+        _ => None
+    }.ok_or_else(|| invalid_field(name))?;
+    u128::try_from(number).ok()
+        .and_then(|number| T::try_from(number).ok())
+        .ok_or_else(|| TonErrorKind::InvalidData(
+            format!("Multisig output field `{}` is out of range: {}", name, value[name])).into())
+}
+
+fn get_uint256(value: &Value, name: &str) -> TonResult<String> {
+    let string = get_str(value, name)?;
+    let hex = string.trim_start_matches("0x");
+    if hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid_field(name));
+    }
+    Ok(format!("{:0>64}", hex.to_lowercase()))
+}
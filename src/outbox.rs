@@ -0,0 +1,214 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{EncodedMessage, JsonValue, MessageProcessingState, ResultOfRun, TonContracts};
+use crate::error::*;
+use crate::retry::{has_error_code, MESSAGE_EXPIRED_CODE};
+use std::path::{Path, PathBuf};
+
+/// Core error codes meaning that message will never be processed: message expired (1006),
+/// account has no code (1015) or not enough balance (1016), contract rejected message (3025)
+pub const OUTBOX_FINAL_ERROR_CODES: &[isize] = &[MESSAGE_EXPIRED_CODE, 1015, 1016, 3025];
+
+/// Message sent through `Outbox` and waiting for processing result
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingMessage {
+    pub message: EncodedMessage,
+    /// State returned by `send_message`. `None` if process stopped before message was sent
+    pub processing_state: Option<MessageProcessingState>,
+    pub abi: Option<serde_json::Value>,
+    pub function_name: Option<String>,
+}
+
+/// Final outcome of pending message processing
+#[derive(Debug)]
+pub struct OutboxOutcome {
+    pub message_id: String,
+    pub function_name: Option<String>,
+    pub result: TonResult<ResultOfRun>,
+}
+
+/// Persistent storage of pending messages
+pub trait OutboxStore {
+    /// Insert or replace pending message
+    fn save(&self, message: &PendingMessage) -> TonResult<()>;
+    fn remove(&self, message_id: &str) -> TonResult<()>;
+    fn load_all(&self) -> TonResult<Vec<PendingMessage>>;
+}
+
+/// Outbox store keeping every pending message in a separate JSON file in given directory
+pub struct FileOutboxStore {
+    dir: PathBuf,
+}
+
+impl FileOutboxStore {
+    /// Create store in `dir`. Directory is created if it doesn't exist
+    pub fn new(dir: impl AsRef<Path>) -> TonResult<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(FileOutboxStore { dir: dir.as_ref().to_owned() })
+    }
+
+    fn file_path(&self, message_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", message_id))
+    }
+}
+
+impl OutboxStore for FileOutboxStore {
+    fn save(&self, message: &PendingMessage) -> TonResult<()> {
+        // write to temporary file first so crash never leaves partially written message
+        let path = self.file_path(&message.message.message_id);
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, serde_json::to_vec_pretty(message)?)?;
+        Ok(std::fs::rename(temp_path, path)?)
+    }
+
+    fn remove(&self, message_id: &str) -> TonResult<()> {
+        let path = self.file_path(message_id);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn load_all(&self) -> TonResult<Vec<PendingMessage>> {
+        let mut messages = vec![];
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+                messages.push(serde_json::from_slice(&std::fs::read(path)?)?);
+            }
+        }
+        Ok(messages)
+    }
+}
+
+/// Sends messages keeping their processing state in persistent store, so waiting for results
+/// can be resumed after process restart with `resume`
+pub struct Outbox<S: OutboxStore = FileOutboxStore> {
+    store: S,
+}
+
+impl<S: OutboxStore> Outbox<S> {
+    pub fn new(store: S) -> Self {
+        Outbox { store }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Messages which processing result is not known yet
+    pub fn pending(&self) -> TonResult<Vec<PendingMessage>> {
+        self.store.load_all()
+    }
+
+    /// Persist and send message without waiting for processing result
+    pub fn send(
+        &self,
+        contracts: &TonContracts,
+        message: EncodedMessage,
+        abi: Option<JsonValue>,
+        function_name: Option<&str>,
+    ) -> TonResult<PendingMessage> {
+        let mut pending = PendingMessage {
+            message,
+            processing_state: None,
+            abi: abi.map(|abi| abi.to_value()).transpose()?,
+            function_name: function_name.map(|name| name.to_owned()),
+        };
+        self.store.save(&pending)?;
+
+        pending.processing_state = Some(contracts.send_message(pending.message.clone())?);
+        self.store.save(&pending)?;
+
+        Ok(pending)
+    }
+
+    /// Persist and send message then wait for processing result
+    pub fn process(
+        &self,
+        contracts: &TonContracts,
+        message: EncodedMessage,
+        abi: Option<JsonValue>,
+        function_name: Option<&str>,
+        infinite_wait: bool,
+    ) -> TonResult<ResultOfRun> {
+        let pending = self.send(contracts, message, abi, function_name)?;
+        self.wait(contracts, pending, infinite_wait)
+    }
+
+    /// Wait for pending message processing result. Message is removed from store when it is
+    /// processed or fails with final error (see `is_final_error`). Other errors (e.g. network
+    /// ones) leave message in store with processing state updated if error contains one
+    pub fn wait(
+        &self,
+        contracts: &TonContracts,
+        pending: PendingMessage,
+        infinite_wait: bool,
+    ) -> TonResult<ResultOfRun> {
+        self.wait_pending(contracts, pending, infinite_wait)?
+    }
+
+    /// Resume waiting for all pending messages and report their outcomes. Fails only if store
+    /// can not be accessed, message processing errors are reported in outcomes
+    pub fn resume(&self, contracts: &TonContracts, infinite_wait: bool) -> TonResult<Vec<OutboxOutcome>> {
+        self.store.load_all()?
+            .into_iter()
+            .map(|pending| {
+                let message_id = pending.message.message_id.clone();
+                let function_name = pending.function_name.clone();
+                let result = self.wait_pending(contracts, pending, infinite_wait)?;
+                Ok(OutboxOutcome { message_id, function_name, result })
+            })
+            .collect()
+    }
+
+    // outer result contains store errors, inner one - message processing result
+    fn wait_pending(
+        &self,
+        contracts: &TonContracts,
+        pending: PendingMessage,
+        infinite_wait: bool,
+    ) -> TonResult<TonResult<ResultOfRun>> {
+        let abi = pending.abi.clone().map(JsonValue::from);
+        let function_name = pending.function_name.as_deref();
+        let result = match pending.processing_state.clone() {
+            Some(state) => contracts.wait_for_transaction(
+                pending.message.clone(), abi, function_name, state, infinite_wait),
+            // message could be sent or not before process stopped, so send it again
+            None => contracts.process_message(
+                pending.message.clone(), abi, function_name, infinite_wait),
+        };
+
+        match &result {
+            Ok(_) => self.store.remove(&pending.message.message_id)?,
+            Err(err) if is_final_error(err) => self.store.remove(&pending.message.message_id)?,
+            Err(TonError(TonErrorKind::InnerSdkError(InnerSdkError {
+                message_processing_state: Some(state), ..
+            }), _)) => {
+                self.store.save(&PendingMessage {
+                    processing_state: Some(state.clone()),
+                    ..pending
+                })?;
+            }
+            // keep message with previous processing state to resume waiting later
+            Err(_) => {}
+        }
+
+        Ok(result)
+    }
+}
+
+/// Check if message processing error is final, so there is no sense to wait for message again.
+/// Errors of aborted transactions and errors with `OUTBOX_FINAL_ERROR_CODES` are final
+pub fn is_final_error(error: &TonError) -> bool {
+    if let TonErrorKind::InnerSdkError(err) = error.kind() {
+        // aborted transaction is reported with its id
+        if !err.data["transaction_id"].is_null() {
+            return true;
+        }
+    }
+    OUTBOX_FINAL_ERROR_CODES.iter().any(|code| has_error_code(error, *code))
+}
@@ -0,0 +1,43 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::MessageProcessingState;
+
+/// Message processing progress reported by `*_with_events` functions
+#[derive(Clone, Debug)]
+pub enum ProcessingEvent {
+    /// New message was created. Reported by `deploy_with_events` for every attempt
+    MessageEncoded {
+        message_id: String,
+        expire: Option<u32>,
+    },
+    /// Message was sent to the node. `state` can be used to resume waiting with
+    /// `wait_for_transaction`
+    MessageSent {
+        message_id: String,
+        state: MessageProcessingState,
+    },
+    /// Shard block produced after message was sent was fetched while waiting for the message
+    /// transaction. `state` points to this block and can be used to resume waiting
+    BlockFetched {
+        message_id: String,
+        block_id: String,
+        state: MessageProcessingState,
+    },
+    TransactionReceived {
+        message_id: String,
+        transaction: serde_json::Value,
+    },
+    /// Processing failed with retryable error and will be repeated after `delay`
+    Retry {
+        attempt: u8,
+        error: String,
+        delay: std::time::Duration,
+    },
+    /// Message expired without being processed
+    Expired {
+        message_id: String,
+        state: Option<MessageProcessingState>,
+    },
+}
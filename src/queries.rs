@@ -39,7 +39,7 @@ pub(crate) struct SubscribeHandle {
     pub handle: StreamHandle
 }
 
-type StreamHandle = u32;
+pub(crate) type StreamHandle = u32;
 
 /// GraphQL answers sorting direction
 #[derive(Serialize, Deserialize)]
@@ -130,15 +130,26 @@ impl TonQueriesCollection {
 
     /// Subscribe for object updates. Returns `Stream` containing objects states
     pub fn subscribe<'a>(&'a self, filter: JsonValue, result: &str) -> TonResult<Box<dyn Stream<Item=Value, Error=TonError> + 'a>> {
+        let handle = self.subscribe_handle(filter, result)?;
+        Ok(Box::new(SubscribeStream { collection: self, handle }))
+    }
+
+    pub(crate) fn subscribe_handle(&self, filter: JsonValue, result: &str) -> TonResult<StreamHandle> {
         let result: SubscribeHandle = Interop::json_request(self.context, "queries.subscribe", ParamsOfSubscribe {
             table: self.name.to_owned(),
             filter: filter.to_string(),
             result: result.to_owned()
         })?;
-        Ok(Box::new(SubscribeStream { collection: self, handle: result.handle }))
+        Ok(result.handle)
+    }
+
+    pub(crate) fn unsubscribe(&self, handle: StreamHandle) -> TonResult<()> {
+        Interop::json_request(self.context, "queries.unsubscribe", SubscribeHandle {
+            handle
+        })
     }
 
-    fn get_next(&self, handle: StreamHandle) -> TonResult<Value> {
+    pub(crate) fn get_next(&self, handle: StreamHandle) -> TonResult<Value> {
         let result: SingleResult = Interop::json_request(self.context, "queries.get.next", SubscribeHandle {
             handle
         })?;
@@ -0,0 +1,183 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::error::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Core error code for expired message
+pub const MESSAGE_EXPIRED_CODE: isize = 1006;
+
+/// Default core `message_retries_count`
+pub const DEFAULT_MESSAGE_RETRIES_COUNT: u8 = 5;
+
+/// Delay between message processing attempts
+#[derive(Clone, Debug, PartialEq)]
+pub enum Backoff {
+    /// Retry immediately
+    None,
+    Fixed(Duration),
+    /// Delay is `initial * factor^retry_index` but not greater than `max`. Negative or NaN
+    /// factor gives zero delay
+    Exponential {
+        initial: Duration,
+        factor: f32,
+        max: Duration,
+    },
+}
+
+impl Backoff {
+    /// Delay before retry with given index (0 for the first retry)
+    pub fn delay(&self, retry_index: u8) -> Duration {
+        match self {
+            Backoff::None => Duration::from_millis(0),
+            Backoff::Fixed(delay) => *delay,
+            Backoff::Exponential { initial, factor, max } => {
+                let delay = initial.as_secs_f64() * (*factor as f64).powi(retry_index as i32);
+                if delay.is_nan() || delay <= 0.0 {
+                    Duration::from_millis(0)
+                } else if delay < max.as_secs_f64() {
+                    Duration::from_secs_f64(delay)
+                } else {
+                    *max
+                }
+            }
+        }
+    }
+}
+
+/// Information about failed attempt passed to `RetryPolicy` callback
+#[derive(Debug)]
+pub struct RetryAttempt<'a> {
+    /// Index of the next attempt. The first attempt has index 0
+    pub attempt: u8,
+    pub error: &'a TonError,
+    pub delay: Duration,
+}
+
+type RetryCallback = Arc<dyn Fn(&RetryAttempt) + Send + Sync>;
+
+/// Client-side message processing retry policy used by `run`, `deploy` and `process_message`.
+///
+/// `run` and `deploy` create new message with increasing `try_index` for every attempt, so message
+/// expiration time grows according to `message_expiration_timeout_grow_factor`. `process_message`
+/// sends the same message again, so it never retries expired message and the policy is useful only
+/// for errors not caused by message expiration.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts including the first one
+    pub max_attempts: u8,
+    pub backoff: Backoff,
+    /// Core error codes which cause retry. Codes of original error (e.g. expiration reason of
+    /// failed transaction) are checked too
+    pub retry_codes: Vec<isize>,
+    on_retry: Option<RetryCallback>,
+}
+
+impl RetryPolicy {
+    /// Policy retrying expired messages without delay
+    pub fn new(max_attempts: u8) -> Self {
+        RetryPolicy {
+            max_attempts,
+            backoff: Backoff::None,
+            retry_codes: vec![MESSAGE_EXPIRED_CODE],
+            on_retry: None,
+        }
+    }
+
+    /// Policy with single attempt
+    pub fn no_retries() -> Self {
+        Self::new(1)
+    }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn with_retry_codes(mut self, retry_codes: Vec<isize>) -> Self {
+        self.retry_codes = retry_codes;
+        self
+    }
+
+    /// Set callback called before every retry
+    pub fn on_retry(mut self, callback: impl Fn(&RetryAttempt) + Send + Sync + 'static) -> Self {
+        self.on_retry = Some(Arc::new(callback));
+        self
+    }
+
+    /// Check if error can be fixed by retrying
+    pub fn is_retryable(&self, error: &TonError) -> bool {
+        self.retry_codes.iter().any(|code| has_error_code(error, *code))
+    }
+
+    /// Call `f` with attempt index until it succeeds, fails with non-retryable error or attempts
+    /// are exhausted
+    pub(crate) fn execute<T>(&self, f: impl FnMut(u8) -> TonResult<T>) -> TonResult<T> {
+        self.execute_with_notify(f, |_| {})
+    }
+
+    /// Same as `execute` but also calls `notify` before every retry
+    pub(crate) fn execute_with_notify<T>(
+        &self,
+        f: impl FnMut(u8) -> TonResult<T>,
+        notify: impl Fn(&RetryAttempt),
+    ) -> TonResult<T> {
+        self.execute_filtered(f, notify, |_| true)
+    }
+
+    /// Same as `execute_with_notify` but retries only errors accepted by `filter` in addition
+    /// to policy retry codes
+    pub(crate) fn execute_filtered<T>(
+        &self,
+        mut f: impl FnMut(u8) -> TonResult<T>,
+        notify: impl Fn(&RetryAttempt),
+        filter: impl Fn(&TonError) -> bool,
+    ) -> TonResult<T> {
+        let mut attempt = 0;
+        loop {
+            match f(attempt) {
+                Err(error) if attempt + 1 < self.max_attempts && self.is_retryable(&error) && filter(&error) => {
+                    let delay = self.backoff.delay(attempt);
+                    attempt += 1;
+                    let retry = RetryAttempt { attempt, error: &error, delay };
+                    if let Some(callback) = &self.on_retry {
+                        callback(&retry);
+                    }
+                    notify(&retry);
+                    std::thread::sleep(delay);
+                }
+                result => return result
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Policy retrying expired messages `DEFAULT_MESSAGE_RETRIES_COUNT` times like core does
+    fn default() -> Self {
+        Self::new(DEFAULT_MESSAGE_RETRIES_COUNT + 1)
+    }
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .field("retry_codes", &self.retry_codes)
+            .field("on_retry", &self.on_retry.is_some())
+            .finish()
+    }
+}
+
+/// Check if error is core error with given code or is caused by such error
+pub(crate) fn has_error_code(error: &TonError, code: isize) -> bool {
+    match error.kind() {
+        TonErrorKind::InnerSdkError(err) => {
+            err.code == code || err.data["original_error"]["code"].as_i64() == Some(code as i64)
+        }
+        _ => false
+    }
+}
@@ -0,0 +1,398 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{parse_message, AccountSnapshot, Cell, DecodedEvent, DeployParams, Ed25519KeyPair, EncodedMessage, Grams,
+    JsonValue, LocalClock, LocalRunContext, MessageBuilder, MessageType, ParsedMessage, ResultOfLocalRun,
+    RunParams, TonAddress, TonContracts, TransactionFees};
+use crate::abi::abi_header;
+use crate::error::*;
+use crate::events::{abi_event_names, EXT_OUT_MESSAGE_TYPE};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+
+// seconds external message sent with `call` stays valid after clock time
+const MESSAGE_EXPIRATION_TIMEOUT: u32 = 40;
+
+/// Transaction emulated in sandbox
+#[derive(Debug, PartialEq)]
+pub struct SandboxTransaction {
+    /// Account processed the message
+    pub address: TonAddress,
+    /// Id of inbound message
+    pub message_id: String,
+    /// Called function if message was sent with ABI
+    pub function_name: Option<String>,
+    /// Decoded function output. `null` if message was sent without ABI
+    pub output: Value,
+    pub fees: Option<TransactionFees>,
+    /// Time and logical time transaction was emulated with
+    pub context: LocalRunContext,
+    /// Messages produced by transaction
+    pub out_messages: Vec<ParsedMessage>,
+    /// Events emitted by transaction. Events are decoded if ABI of the account is known to sandbox
+    pub events: Vec<DecodedEvent>,
+    /// Core error if contract failed to process the message. Account state is not changed then
+    pub error: Option<InnerSdkError>,
+}
+
+struct QueuedMessage {
+    message: EncodedMessage,
+    abi: Option<Value>,
+    function_name: Option<String>,
+}
+
+/// Set of accounts kept in memory to emulate sequence of transactions locally, like a local
+/// blockchain for tests. Every processed message updates stored account state and is recorded
+/// as `SandboxTransaction`.
+///
+/// Internal messages produced by contracts are put to the queue along with messages sent with
+/// `send` and are processed by `run_queue`. Messages to accounts missing in sandbox are kept as
+/// undelivered. External outbound messages are decoded as events with ABI the account was
+/// deployed or called with. Messages failed to be processed by contract are recorded as
+/// transactions with `error` and don't stop queue processing.
+///
+/// Routing requires core reporting messages produced by emulated transaction
+/// (`ResultOfLocalRun::out_messages`). Processing fails with `InternalError` if they are missing,
+/// unless routing is disabled with `set_message_routing`.
+///
+/// Transactions are emulated with time and logical time of sandbox `LocalClock`, which starts at
+/// current system time and can be advanced with `clock_mut`
+pub struct Sandbox<'a> {
+    contracts: &'a TonContracts,
+    accounts: HashMap<String, AccountSnapshot>,
+    queue: VecDeque<QueuedMessage>,
+    undelivered: Vec<EncodedMessage>,
+    transactions: Vec<SandboxTransaction>,
+    events: Vec<DecodedEvent>,
+    // ABIs of accounts used to decode their events
+    abis: HashMap<String, Value>,
+    clock: LocalClock,
+    routing: bool,
+}
+
+impl<'a> Sandbox<'a> {
+    pub fn new(contracts: &'a TonContracts) -> Self {
+        Self::with_clock(contracts, LocalClock::now())
+    }
+
+    pub fn with_clock(contracts: &'a TonContracts, clock: LocalClock) -> Self {
+        Sandbox {
+            contracts,
+            accounts: HashMap::new(),
+            queue: VecDeque::new(),
+            undelivered: vec![],
+            transactions: vec![],
+            events: vec![],
+            abis: HashMap::new(),
+            clock,
+            routing: true,
+        }
+    }
+
+    /// Enable or disable routing of messages produced by contracts. Enabled by default.
+    /// Disabled routing allows to use sandbox with core not reporting produced messages
+    pub fn set_message_routing(&mut self, enabled: bool) {
+        self.routing = enabled;
+    }
+
+    pub fn clock(&self) -> &LocalClock {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut LocalClock {
+        &mut self.clock
+    }
+
+    /// Add account to sandbox replacing the one with the same address
+    pub fn add_account(&mut self, snapshot: AccountSnapshot) -> TonResult<TonAddress> {
+        let address = snapshot.address()?;
+        self.accounts.insert(address.to_string(), snapshot);
+        Ok(address)
+    }
+
+    /// Add uninitialized account holding `balance` nanograms. Contract can be deployed to such
+    /// account with `deploy` or message with state init
+    pub fn add_uninit_account(&mut self, address: &TonAddress, balance: Grams) -> TonResult<()> {
+        self.add_account(AccountSnapshot::from_json(serde_json::json!({
+            "id": address.to_string(),
+            "acc_type": 0,
+            "balance": format!("{:#x}", balance.as_nano()),
+            "last_paid": 0,
+        }))?)?;
+        Ok(())
+    }
+
+    pub fn account(&self, address: &TonAddress) -> Option<&AccountSnapshot> {
+        self.accounts.get(&address.to_string())
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = &AccountSnapshot> {
+        self.accounts.values()
+    }
+
+    /// Account balance in nanograms
+    pub fn balance(&self, address: &TonAddress) -> TonResult<Grams> {
+        let account = self.account(address).ok_or(TonErrorKind::NotFound)?.account();
+        match &account["balance"] {
+            Value::String(balance) => balance.parse::<Grams>(),
+            Value::Number(balance) => balance.to_string().parse::<Grams>(),
+            _ => Ok(Grams::zero())
+        }
+    }
+
+    /// Transactions emulated so far in processing order
+    pub fn transactions(&self) -> &[SandboxTransaction] {
+        &self.transactions
+    }
+
+    /// Events emitted by all emulated transactions in processing order
+    pub fn events(&self) -> &[DecodedEvent] {
+        &self.events
+    }
+
+    /// Messages to accounts missing in sandbox
+    pub fn undelivered(&self) -> &[EncodedMessage] {
+        &self.undelivered
+    }
+
+    /// Deploy contract to sandbox. Account is created with `balance` unless it is already present.
+    /// If `constructor_header` is not set, `time` and `expire` header values are taken from
+    /// sandbox clock
+    pub fn deploy(
+        &mut self,
+        params: &DeployParams,
+        keys: &Ed25519KeyPair,
+        balance: Grams,
+    ) -> TonResult<TonAddress> {
+        let abi = params.abi.clone().to_value()?;
+        let address = self.contracts.get_deploy_address(
+            abi.clone().into(), &params.image, params.init_params.clone(), &keys.public, params.workchain_id)?;
+        if self.account(&address).is_none() {
+            self.add_uninit_account(&address, balance)?;
+        }
+        let constructor_header = match &params.constructor_header {
+            Some(header) => Some(header.clone()),
+            None => self.clock_header(&abi)?,
+        };
+        let message = self.contracts.create_deploy_message(
+            abi.clone().into(),
+            &params.image,
+            constructor_header,
+            params.constructor_params.clone(),
+            params.init_params.clone(),
+            keys,
+            params.workchain_id,
+            None)?;
+        succeeded(self.process(
+            QueuedMessage { message, abi: Some(abi), function_name: Some("constructor".to_owned()) })?)?;
+        Ok(address)
+    }
+
+    /// Call contract function with external inbound message and return emulated transaction.
+    /// Error is returned if contract fails, failed transaction is recorded anyway.
+    /// If `header` is not set, `time` and `expire` header values are taken from sandbox clock
+    pub fn call(
+        &mut self,
+        address: &TonAddress,
+        abi: JsonValue,
+        function_name: &str,
+        header: Option<JsonValue>,
+        input: JsonValue,
+        keys: Option<&Ed25519KeyPair>,
+    ) -> TonResult<&SandboxTransaction> {
+        let abi = abi.to_value()?;
+        let header = match header {
+            Some(header) => Some(header),
+            None => self.clock_header(&abi)?,
+        };
+        let message = self.contracts.create_run_message(
+            address, abi.clone().into(), function_name, header, input, keys, None)?;
+        succeeded(self.process(
+            QueuedMessage { message, abi: Some(abi), function_name: Some(function_name.to_owned()) })?)
+    }
+
+    /// Run contract function on current account state and clock time without changing them
+    pub fn run_local(
+        &self,
+        address: &TonAddress,
+        abi: JsonValue,
+        function_name: &str,
+        header: Option<JsonValue>,
+        input: JsonValue,
+        keys: Option<&Ed25519KeyPair>,
+    ) -> TonResult<ResultOfLocalRun> {
+        let snapshot = self.account(address).ok_or(TonErrorKind::NotFound)?;
+        let params = RunParams { abi, function_name: function_name.to_owned(), header, input };
+        self.contracts.run_local_snapshot(snapshot, &params, keys, Some(self.clock.context()), false)
+    }
+
+    /// Run get-method on current account state
+    pub fn run_get(
+        &self,
+        address: &TonAddress,
+        function_name: &str,
+        input: Option<JsonValue>,
+    ) -> TonResult<ResultOfLocalRun> {
+        let snapshot = self.account(address).ok_or(TonErrorKind::NotFound)?;
+        self.contracts.run_get_snapshot(snapshot, function_name, input)
+    }
+
+    /// Put message to queue. Pass `abi` and `function_name` to decode function output
+    pub fn send(
+        &mut self,
+        message: &MessageBuilder,
+        abi: Option<JsonValue>,
+        function_name: Option<&str>,
+    ) -> TonResult<()> {
+        self.queue.push_back(QueuedMessage {
+            message: message.build(self.contracts)?,
+            abi: abi.map(|abi| abi.to_value()).transpose()?,
+            function_name: function_name.map(|name| name.to_owned()),
+        });
+        Ok(())
+    }
+
+    /// Process queued messages in order. Returns number of emulated transactions including
+    /// failed ones
+    pub fn run_queue(&mut self) -> TonResult<usize> {
+        let mut count = 0;
+        while let Some(queued) = self.queue.pop_front() {
+            if self.account(&queued.message.address).is_none() {
+                self.undelivered.push(queued.message);
+                continue;
+            }
+            self.process(queued)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    // message header with clock time, so messages are not expired after clock is advanced
+    fn clock_header(&self, abi: &Value) -> TonResult<Option<JsonValue>> {
+        let mut header = serde_json::Map::new();
+        for param in abi_header(abi)? {
+            match param.as_str() {
+                "time" => { header.insert(param, (self.clock.time() as u64 * 1000).into()); }
+                "expire" => { header.insert(param, (self.clock.time() + MESSAGE_EXPIRATION_TIMEOUT).into()); }
+                _ => {}
+            }
+        }
+        Ok(if header.is_empty() { None } else { Some(Value::Object(header).into()) })
+    }
+
+    fn process(&mut self, queued: QueuedMessage) -> TonResult<&SandboxTransaction> {
+        let address = queued.message.address.clone();
+        let message_id = queued.message.message_id.clone();
+        let account = self.account(&address).ok_or(TonErrorKind::NotFound)?.account().clone();
+        let context = self.clock.next_context();
+        if let Some(abi) = &queued.abi {
+            self.abis.insert(address.to_string(), abi.clone());
+        }
+
+        let result = self.contracts.run_local_msg(
+            &address,
+            Some(account.into()),
+            queued.message,
+            queued.abi.map(|abi| abi.into()),
+            queued.function_name.as_deref(),
+            Some(context.clone()),
+            true);
+        let result = match result {
+            Ok(result) => result,
+            Err(err) => {
+                let error = match err.kind() {
+                    TonErrorKind::InnerSdkError(error) => error.clone(),
+                    _ => return Err(err)
+                };
+                self.transactions.push(SandboxTransaction {
+                    address,
+                    message_id,
+                    function_name: queued.function_name,
+                    output: Value::Null,
+                    fees: None,
+                    context,
+                    out_messages: vec![],
+                    events: vec![],
+                    error: Some(error),
+                });
+                return Ok(self.transactions.last().unwrap());
+            }
+        };
+
+        let produced = match (self.routing, result.out_messages) {
+            (false, _) => vec![],
+            (true, Some(messages)) => messages,
+            (true, None) => return Err(TonErrorKind::InternalError(
+                "Core didn't report messages produced by emulated transaction, \
+                    disable sandbox message routing to use this core version".to_owned()).into())
+        };
+
+        if let Some(mut account) = result.account {
+            if account["id"].is_null() {
+                account["id"] = address.to_string().into();
+            }
+            self.add_account(AccountSnapshot::from_json(account)?)?;
+        }
+
+        let mut out_messages = vec![];
+        let mut events = vec![];
+        for boc in &produced {
+            let boc = base64::decode(boc)?;
+            let message = parse_message(&boc)?;
+            let message_id = self.contracts.get_boc_hash(&boc)?;
+            match (message.message_type, &message.dst, &message.body) {
+                (MessageType::Internal, Some(dst), _) => self.queue.push_back(QueuedMessage {
+                    message: EncodedMessage { message_id, message_body: boc, expire: None, address: dst.clone() },
+                    abi: None,
+                    function_name: None,
+                }),
+                (MessageType::ExternalOut, _, Some(body)) => {
+                    if let Some(event) = self.decode_event(&address, &message_id, body)? {
+                        events.push(event);
+                    }
+                }
+                _ => {}
+            }
+            out_messages.push(message);
+        }
+        self.events.extend(events.iter().cloned());
+
+        self.transactions.push(SandboxTransaction {
+            address,
+            message_id,
+            function_name: queued.function_name,
+            output: result.output,
+            fees: result.fees,
+            context,
+            out_messages,
+            events,
+            error: None,
+        });
+        Ok(self.transactions.last().unwrap())
+    }
+
+    fn decode_event(
+        &self,
+        address: &TonAddress,
+        message_id: &str,
+        body: &Cell,
+    ) -> TonResult<Option<DecodedEvent>> {
+        let abi = match self.abis.get(&address.to_string()) {
+            Some(abi) => abi,
+            None => return Ok(None)
+        };
+        self.contracts.decode_event_message(abi, &abi_event_names(abi), &serde_json::json!({
+            "id": message_id,
+            "msg_type": EXT_OUT_MESSAGE_TYPE,
+            "body": base64::encode(&body.to_boc()?),
+        }))
+    }
+}
+
+fn succeeded(transaction: &SandboxTransaction) -> TonResult<&SandboxTransaction> {
+    match &transaction.error {
+        Some(error) => Err(TonErrorKind::InnerSdkError(error.clone()).into()),
+        None => Ok(transaction)
+    }
+}
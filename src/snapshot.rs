@@ -0,0 +1,91 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{Ed25519KeyPair, JsonValue, LocalRunContext, ResultOfLocalRun, RunParams, TonAddress, TonContracts};
+use crate::error::*;
+use serde_json::Value;
+use std::path::Path;
+
+/// Account fields stored in snapshot. Same fields are accepted by `run_get` and `run_local`
+pub const ACCOUNT_SNAPSHOT_FIELDS: &str = "id acc_type balance balance_other { currency value } last_paid code data";
+
+/// Account state captured from blockchain and stored to file to run get-methods and contract
+/// functions offline with the same result
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    account: Value,
+}
+
+impl AccountSnapshot {
+    /// Create snapshot from account JSON. Account must have `id` field, `code` and `data` fields
+    /// are required to run contract
+    pub fn from_json(account: Value) -> TonResult<Self> {
+        let address = account["id"].as_str()
+            .ok_or_else(|| TonErrorKind::InvalidData("Account snapshot has no `id` field".to_owned()))?;
+        TonAddress::from_str(address)?;
+        Ok(AccountSnapshot { account })
+    }
+
+    /// Query current account state
+    pub fn fetch(contracts: &TonContracts, address: &TonAddress) -> TonResult<Self> {
+        let account = contracts.query(
+            "accounts",
+            serde_json::json!({ "id": { "eq": address.to_string() } }),
+            ACCOUNT_SNAPSHOT_FIELDS,
+            Some(1))?
+            .pop()
+            .ok_or(TonErrorKind::NotFound)?;
+        Self::from_json(account)
+    }
+
+    /// Save snapshot to JSON file. File format is the same as accepted by `from_json`
+    pub fn save(&self, path: impl AsRef<Path>) -> TonResult<()> {
+        Ok(std::fs::write(path, serde_json::to_vec_pretty(&self.account)?)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> TonResult<Self> {
+        Self::from_json(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn address(&self) -> TonResult<TonAddress> {
+        TonAddress::from_str(self.account["id"].as_str().unwrap_or_default())
+    }
+
+    pub fn account(&self) -> &Value {
+        &self.account
+    }
+}
+
+impl TonContracts {
+    /// Run get-method on account snapshot without network requests
+    pub fn run_get_snapshot(
+        &self,
+        snapshot: &AccountSnapshot,
+        function_name: &str,
+        input: Option<JsonValue>,
+    ) -> TonResult<ResultOfLocalRun> {
+        self.run_get(None, Some(snapshot.account.clone().into()), function_name, input)
+    }
+
+    /// Run contract function locally on account snapshot without network requests
+    pub fn run_local_snapshot(
+        &self,
+        snapshot: &AccountSnapshot,
+        params: &RunParams,
+        keys: Option<&Ed25519KeyPair>,
+        context: Option<LocalRunContext>,
+        emulate_transaction: bool,
+    ) -> TonResult<ResultOfLocalRun> {
+        self.run_local(
+            &snapshot.address()?,
+            Some(snapshot.account.clone().into()),
+            params.abi.clone(),
+            &params.function_name,
+            params.header.clone(),
+            params.input.clone(),
+            keys,
+            context,
+            emulate_transaction)
+    }
+}
@@ -4,16 +4,31 @@
 
 use std::env;
 use crate::{TonClient, Ed25519KeyPair, Ed25519Public, TonAddress, ResultOfGetDeployData, JsonValue};
+use crate::{DeployParams, Giver, Grams, NodeSeGiver, WalletGiver};
 mod test_piggy;
 mod test_hello;
 mod test_run_get;
 mod test_errors;
 mod test_local_run;
+mod test_grams;
+mod test_int256;
+mod test_cell;
+mod test_unsigned;
+mod test_envelope;
+mod test_outbox;
+mod test_retry;
+mod test_multisig;
+mod test_transfer;
+mod test_events;
+mod test_message_decoder;
+mod test_abi_registry;
+mod test_abi;
+mod test_sandbox;
+mod test_clock;
 
 const ROOT_CONTRACTS_PATH: &str = "src/tests/contracts/";
 
 lazy_static::lazy_static! {
-    static ref GIVER_ADDRESS: TonAddress = TonAddress::from_str("0:841288ed3b55d9cdafa806807f02a0ae0c169aa5edfe88a789a6482429756a94").unwrap();
     static ref WALLET_ADDRESS: TonAddress = TonAddress::from_str("0:2bb4a0e8391e7ea8877f4825064924bd41ce110fce97e939d3323999e1efbb13").unwrap();
 	static ref WALLET_KEYS: Option<Ed25519KeyPair> = get_wallet_keys();
 
@@ -29,8 +44,6 @@ lazy_static::lazy_static! {
 	pub static ref PIGGY_BANK_ABI: String = std::fs::read_to_string(CONTRACTS_PATH.clone() + "Piggy.abi.json").unwrap();
     pub static ref WALLET_ABI: String = std::fs::read_to_string(CONTRACTS_PATH.clone() + "LimitWallet.abi.json").unwrap();
     pub static ref SIMPLE_WALLET_ABI: String = std::fs::read_to_string(CONTRACTS_PATH.clone() + "Wallet.abi.json").unwrap();
-	pub static ref GIVER_ABI: String = std::fs::read_to_string(ROOT_CONTRACTS_PATH.to_owned() + "Giver.abi.json").unwrap();
-	pub static ref GIVER_WALLET_ABI: String = std::fs::read_to_string(ROOT_CONTRACTS_PATH.to_owned() + "GiverWallet.abi.json").unwrap();
 	pub static ref HELLO_ABI: String = std::fs::read_to_string(CONTRACTS_PATH.clone() + "Hello.abi.json").unwrap();
 
     pub static ref SUBSCRIBE_IMAGE: Vec<u8> = std::fs::read(CONTRACTS_PATH.clone() + "Subscription.tvc").unwrap();
@@ -38,6 +51,10 @@ lazy_static::lazy_static! {
 	pub static ref WALLET_IMAGE: Vec<u8> = std::fs::read(CONTRACTS_PATH.clone() + "LimitWallet.tvc").unwrap();
 	pub static ref SIMPLE_WALLET_IMAGE: Vec<u8> = std::fs::read(CONTRACTS_PATH.clone() + "Wallet.tvc").unwrap();
 	pub static ref HELLO_IMAGE: Vec<u8> = std::fs::read(CONTRACTS_PATH.clone() + "Hello.tvc").unwrap();
+
+	// multisig images are looked up next to bundled ABIs, tests using them are skipped if absent
+	pub static ref SAFE_MULTISIG_IMAGE: Option<Vec<u8>> = std::fs::read("src/contracts/SafeMultisigWallet.tvc").ok();
+	pub static ref SETCODE_MULTISIG_IMAGE: Option<Vec<u8>> = std::fs::read("src/contracts/SetcodeMultisigWallet.tvc").ok();
 }
 
 fn get_wallet_keys() -> Option<Ed25519KeyPair> {
@@ -237,64 +254,26 @@ fn test_call_aborted_transaction() {
 	};
 }
 
-pub fn get_grams_from_giver(ton: &TonClient, account: &TonAddress, value: Option<u64>) {
+pub fn giver() -> Box<dyn Giver> {
 	if *NODE_SE {
-		ton.contracts.run(
-			&GIVER_ADDRESS,
-			GIVER_ABI.to_string().into(),
-			"sendGrams",
-			None,
-			json!({
-				"dest": account.to_string(),
-				"amount": value.unwrap_or(500_000_000u64)
-			}).to_string().into(),
-			None).unwrap();
+		Box::new(NodeSeGiver::new())
 	} else {
-		ton.contracts.run(
-			&WALLET_ADDRESS,
-			GIVER_WALLET_ABI.to_string().into(),
-			"sendTransaction",
-			None,
-			json!({
-				"dest": account.to_string(),
-				"value": value.unwrap_or(500_000_000u64),
-				"bounce": false
-			}).to_string().into(),
-			WALLET_KEYS.as_ref()).unwrap();
+		Box::new(WalletGiver::new(WALLET_ADDRESS.clone(), WALLET_KEYS.clone().unwrap()))
 	}
+}
 
-	// wait for grams recieving
-	let wait_result = ton.queries.accounts.wait_for(
-		json!({
-			"id": { "eq": account.to_string() },
-			"balance": { "gt": "0" }
-		}).into(),
-		"id balance"
-	).unwrap();
-
-	println!("wait result {}", wait_result);
+pub fn get_grams_from_giver(ton: &TonClient, account: &TonAddress, value: Option<u64>) {
+	ton.fund_account(giver().as_ref(), account, Grams::from(value.unwrap_or(500_000_000u64))).unwrap();
 }
 
 pub fn deploy_with_giver(
 	client: &TonClient, abi: JsonValue, image: &[u8], params: JsonValue, keypair: &Ed25519KeyPair
 ) -> TonAddress {
-    let prepared_address = client.contracts.get_deploy_address(
-        abi.clone(),
-        image,
-        None,
-        &keypair.public,
-        0).expect("Couldn't create key pair");
-
-    get_grams_from_giver(client, &prepared_address, None);
-
-    client.contracts.deploy(
-        abi,
-        image,
-        None,
-        params,
-        None,
-        keypair,
-        0)
+    client.deploy_funded(
+        giver().as_ref(),
+        Grams::from(500_000_000u64),
+        &DeployParams::new(abi, image, params),
+        keypair)
         .expect("Couldn't deploy contract")
     .address
 }
@@ -324,6 +303,36 @@ fn test_decode_input() {
     }));
 }
 
+#[test]
+fn test_encode_message_body() {
+	if *ABI_VERSION == 1 {
+		return
+	}
+
+	let ton = TonClient::default().unwrap();
+	let keys = ton.crypto.generate_ed25519_keys().unwrap();
+	let params = json!({
+        "period": "86400",
+        "pubkey": "0xe31e8aac98f0032b92e37618aba8a3117a983cd957bae120de29e6fd7ceb8e9d",
+        "subscriptionId": "0x2222222222222222222222222222222222222222222222222222222222222222",
+        "to": "0:f9c4f95cad2ec18460caf07c280f001d5d049b933d399af7ad4c40f77d4b3030",
+        "value": "5000000000"
+    });
+
+	for (internal, keys) in [(true, None), (false, Some(&keys))].iter().cloned() {
+		let body = ton.contracts.encode_message_body(
+			SUBSCRIBE_ABI.to_string().into(), "subscribe", None, params.clone().into(), internal, keys
+		).unwrap();
+
+		let result = ton.contracts.decode_input_message_body(
+			SUBSCRIBE_ABI.to_string().into(), &body, internal
+		).expect("Couldn't parse body");
+
+		assert_eq!(result.function, "subscribe");
+		assert_eq!(result.output, params);
+	}
+}
+
 #[test]
 fn test_init_state() {
 
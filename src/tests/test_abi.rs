@@ -0,0 +1,241 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::{AbiInfo, SAFE_MULTISIG_ABI};
+
+#[test]
+fn test_abi_info() {
+    let info = AbiInfo::parse(SUBSCRIBE_ABI.to_string().into()).unwrap();
+    assert_eq!(info.version, *ABI_VERSION);
+
+    let subscribe = info.function("subscribe").unwrap();
+    assert_eq!(subscribe.signature(), "subscribe(uint256,uint256,address,uint64,uint32)");
+    assert_eq!(subscribe.inputs[2].name, "to");
+    assert_eq!(subscribe.input_id & 0x80000000, 0);
+    assert_eq!(subscribe.output_id, subscribe.input_id | 0x80000000);
+    assert_eq!(info.function_by_id(subscribe.output_id).unwrap().name, "subscribe");
+
+    let get_subscription = info.function("getSubscription").unwrap();
+    assert_eq!(
+        get_subscription.full_signature(info.version),
+        format!("getSubscription(uint256)((uint256,address,uint64,uint32,uint32,uint8))v{}", info.version));
+    assert_eq!(get_subscription.outputs[0].components.len(), 6);
+
+    assert!(info.function("unknown").is_none());
+}
+
+#[test]
+fn test_abi_info_ids() {
+    let info = AbiInfo::parse(SAFE_MULTISIG_ABI.into()).unwrap();
+    assert_eq!(info.version, 2);
+    assert_eq!(info.header, vec!["pubkey", "time", "expire"]);
+
+    let get_transactions = info.function("getTransactions").unwrap();
+    assert_eq!(get_transactions.input_id, 0x73122f72);
+    assert_eq!(get_transactions.output_id, 0xf3122f72);
+
+    let event = info.event_by_id(0x7d729cc8).unwrap();
+    assert_eq!(event.name, "TransferAccepted");
+    assert_eq!(event.signature(), "TransferAccepted(bytes)");
+
+    let info = AbiInfo::parse(json!({
+        "ABI version": 1,
+        "functions": [{ "name": "explicit", "id": "0x0000000a", "inputs": [], "outputs": [] }]
+    }).into()).unwrap();
+    assert_eq!(info.header, vec!["time"]);
+    assert_eq!(info.function_by_id(10).unwrap().signature(), "explicit()");
+
+    assert!(AbiInfo::parse(json!({ "ABI version": 3 }).into()).is_err());
+}
+
+#[test]
+fn test_coerce_input() {
+    let info = AbiInfo::parse(SAFE_MULTISIG_ABI.into()).unwrap();
+    let submit = info.function("submitTransaction").unwrap();
+
+    let address = "kf/8uRo6OBbQ97jCx2EIuKm8Wmt6Vb15+KsQHFLbKSMiYIny";
+    let input = submit.coerce_input(&json!({
+        "dest": address,
+        "value": 1_000_000_000u64,
+        "bounce": false,
+        "allBalance": false,
+        "payload": "",
+    })).unwrap();
+    assert_eq!(input["dest"], "-1:fcb91a3a3816d0f7b8c2c76108b8a9bc5a6b7a55bd79f8ab101c52db29232260");
+    assert_eq!(input["value"], "1000000000");
+
+    let error = submit.coerce_input(&json!({
+        "dest": "0:01",
+        "value": 1,
+        "bounce": false,
+        "allBalance": false,
+        "payload": "",
+    })).unwrap_err();
+    assert_eq!(error.to_string(), "Invalid input parameter `dest`: invalid address `0:01`");
+
+    let error = submit.coerce_input(&json!({ "dest": address })).unwrap_err();
+    assert_eq!(error.to_string(), "Invalid input parameter `value`: missing parameter");
+
+    let error = info.function("confirmTransaction").unwrap()
+        .coerce_input(&json!({ "transactionId": "-1" })).unwrap_err();
+    assert_eq!(error.to_string(), "Invalid input parameter `transactionId`: value -1 does not fit into uint64");
+}
+
+#[test]
+fn test_validate_input() {
+    let ton = TonClient::default().unwrap();
+    let input = json!({
+        "dest": "0:01",
+        "value": 1,
+        "bounce": false,
+        "allBalance": false,
+        "payload": "",
+    });
+    let create_message = || ton.contracts.create_run_message(
+        &WALLET_ADDRESS, SAFE_MULTISIG_ABI.into(), "submitTransaction", None, input.clone().into(), None, None);
+
+    // input is checked by core by default
+    assert!(!ton.contracts.validate_input());
+    let error = create_message().unwrap_err();
+    assert!(!matches!(error.kind(), crate::error::TonErrorKind::InvalidInputParam(..)));
+
+    ton.contracts.set_validate_input(true);
+    let error = create_message().unwrap_err();
+    assert_eq!(error.to_string(), "Invalid input parameter `dest`: invalid address `0:01`");
+}
+
+#[test]
+fn test_coerce_nested_input() {
+    let info = AbiInfo::parse(json!({
+        "ABI version": 2,
+        "functions": [{
+            "name": "setup",
+            "inputs": [
+                { "name": "owners", "type": "tuple[]", "components": [
+                    { "name": "index", "type": "uint8" },
+                    { "name": "pubkey", "type": "uint256" }
+                ]},
+                { "name": "delta", "type": "int8" },
+                { "name": "hash", "type": "fixedbytes2" },
+                { "name": "limits", "type": "map(uint32,uint128)" }
+            ],
+            "outputs": []
+        }]
+    }).into()).unwrap();
+    let setup = info.function("setup").unwrap();
+
+    let input = setup.coerce_input(&json!({
+        "owners": [[0, "0x01"], { "index": 1, "pubkey": "2" }],
+        "delta": -128,
+        "hash": [1, 255],
+        "limits": { "86400": 1000 },
+    })).unwrap();
+    assert_eq!(input, json!({
+        "owners": [{ "index": "0", "pubkey": "0x01" }, { "index": "1", "pubkey": "2" }],
+        "delta": "-128",
+        "hash": "01ff",
+        "limits": { "86400": "1000" },
+    }));
+
+    let invalid = |input: serde_json::Value| setup.coerce_input(&input).unwrap_err().to_string();
+    assert_eq!(
+        invalid(json!({ "owners": [{ "index": 256, "pubkey": "0" }], "delta": 0, "hash": "0000", "limits": {} })),
+        "Invalid input parameter `owners[0].index`: value 256 does not fit into uint8");
+    assert_eq!(
+        invalid(json!({ "owners": [], "delta": 128, "hash": "0000", "limits": {} })),
+        "Invalid input parameter `delta`: value 128 does not fit into int8");
+    assert_eq!(
+        invalid(json!({ "owners": [], "delta": 0, "hash": "00", "limits": {} })),
+        "Invalid input parameter `hash`: expected 2 bytes, got 1");
+    assert_eq!(
+        invalid(json!({ "owners": [], "delta": 0, "hash": "0000", "limits": { "x": 1 } })),
+        "Invalid input parameter `limits[x]`: invalid integer `x`");
+    assert_eq!(
+        invalid(json!({ "owners": [], "delta": 0, "hash": "0000", "limits": {}, "extra": 1 })),
+        "Invalid input parameter `extra`: unknown parameter");
+}
+
+#[test]
+fn test_coerce_gram_input() {
+    let info = AbiInfo::parse(json!({
+        "ABI version": 2,
+        "functions": [{
+            "name": "send",
+            "inputs": [{ "name": "value", "type": "gram" }],
+            "outputs": []
+        }]
+    }).into()).unwrap();
+    let send = info.function("send").unwrap();
+
+    let max = format!("0x{}", "ff".repeat(15));
+    assert_eq!(send.coerce_input(&json!({ "value": max })).unwrap(), json!({ "value": max }));
+    assert!(send.coerce_input(&json!({ "value": format!("0x1{}", "00".repeat(15)) })).is_err());
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Transaction {
+    #[serde(with = "crate::abi_serde::uint")]
+    id: u64,
+    #[serde(with = "crate::abi_serde::uint")]
+    value: u128,
+    #[serde(with = "crate::abi_serde::int")]
+    delta: i8,
+    dest: crate::TonAddress,
+    #[serde(with = "crate::abi_serde::bytes")]
+    payload: Vec<u8>,
+    #[serde(with = "crate::abi_serde::cell")]
+    body: crate::Cell,
+    bounce: bool,
+}
+
+#[test]
+fn test_output_as() {
+    let body = crate::CellBuilder::new().build().unwrap();
+    let result = crate::ResultOfLocalRun {
+        output: json!({
+            "trans": {
+                "id": "0x36b1f820a400",
+                "value": "1000000000000000000000",
+                "delta": -128,
+                "dest": "0:fcb91a3a3816d0f7b8c2c76108b8a9bc5a6b7a55bd79f8ab101c52db29232260",
+                "payload": "01ff",
+                "body": base64::encode(&body.to_boc().unwrap()),
+                "bounce": true,
+            }
+        }),
+        fees: None,
+        account: None,
+        out_messages: None,
+    };
+
+    #[derive(Deserialize)]
+    struct Output {
+        trans: Transaction,
+    }
+
+    let output: Output = result.output_as().unwrap();
+    assert_eq!(output.trans, Transaction {
+        id: 0x36b1f820a400,
+        value: 1_000_000_000_000_000_000_000,
+        delta: -128,
+        dest: crate::TonAddress::from_str(
+            "0:fcb91a3a3816d0f7b8c2c76108b8a9bc5a6b7a55bd79f8ab101c52db29232260").unwrap(),
+        payload: vec![1, 255],
+        body,
+        bounce: true,
+    });
+
+    #[derive(Debug, Deserialize)]
+    struct Small {
+        #[serde(with = "crate::abi_serde::uint")]
+        #[allow(dead_code)]
+        id: u8,
+    }
+    let error = crate::ResultOfLocalRun { output: json!({ "id": "0x100" }), fees: None, account: None, out_messages: None }
+        .output_as::<Small>()
+        .unwrap_err();
+    assert!(error.to_string().contains("integer 256 is out of range"));
+}
@@ -0,0 +1,97 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::{AbiRegistry, CellBuilder, MessageBodyType, MessageType, SAFE_MULTISIG_ABI};
+use crate::abi::{event_ids, function_ids};
+
+fn registry() -> AbiRegistry {
+    let mut registry = AbiRegistry::new();
+    registry.register("Wallet", SIMPLE_WALLET_ABI.to_string().into()).unwrap();
+    registry.register("LimitWallet", WALLET_ABI.to_string().into()).unwrap();
+    registry.register("Multisig", SAFE_MULTISIG_ABI.into()).unwrap();
+    registry
+}
+
+fn body_with_id(id: u32) -> crate::Cell {
+    let mut builder = CellBuilder::new();
+    builder.append_u32(id).unwrap();
+    builder.build().unwrap()
+}
+
+#[test]
+fn test_abi_ids() {
+    let abi: serde_json::Value = serde_json::from_str(SAFE_MULTISIG_ABI).unwrap();
+
+    let functions = function_ids(&abi).unwrap();
+    let get_transactions = functions.iter().find(|function| function.name == "getTransactions").unwrap();
+    assert_eq!(get_transactions.input_id, 0x73122f72);
+    assert_eq!(get_transactions.output_id, 0xf3122f72);
+
+    let events = event_ids(&abi).unwrap();
+    assert_eq!(events[0].name, "TransferAccepted");
+    assert_eq!(events[0].id, 0x7d729cc8);
+
+    let abi = json!({
+        "ABI version": 2,
+        "functions": [{ "name": "explicit", "id": "0x0000000a", "inputs": [], "outputs": [] }]
+    });
+    let functions = function_ids(&abi).unwrap();
+    assert_eq!((functions[0].input_id, functions[0].output_id), (10, 10));
+}
+
+#[test]
+fn test_registry_matching() {
+    if *ABI_VERSION == 1 {
+        return
+    }
+    let registry = registry();
+
+    // same function in different ABIs
+    let matching = registry.matching(&body_with_id(0x315ef935), MessageType::Internal, None).unwrap();
+    let mut abis: Vec<&str> = matching.iter().map(|(abi, _, _)| abi.as_str()).collect();
+    abis.sort();
+    assert_eq!(abis, vec!["LimitWallet", "Wallet"]);
+    assert!(matching.iter().all(|(_, name, body_type)|
+        name == "sendTransaction" && *body_type == MessageBodyType::Input));
+
+    let matching = registry.matching(&body_with_id(0x7d729cc8), MessageType::ExternalOut, None).unwrap();
+    assert_eq!(matching, vec![("Multisig".to_owned(), "TransferAccepted".to_owned(), MessageBodyType::Event)]);
+
+    let matching = registry.matching(&body_with_id(0xf3122f72), MessageType::ExternalOut, None).unwrap();
+    assert_eq!(matching, vec![("Multisig".to_owned(), "getTransactions".to_owned(), MessageBodyType::Output)]);
+
+    // internal response of the function called by another contract
+    let matching = registry.matching(&body_with_id(0xf3122f72), MessageType::Internal, None).unwrap();
+    assert_eq!(matching, vec![("Multisig".to_owned(), "getTransactions".to_owned(), MessageBodyType::Output)]);
+
+    assert!(registry.matching(&body_with_id(1), MessageType::Internal, None).unwrap().is_empty());
+
+    // code hash restricts candidates to the bound ABI
+    let mut registry = registry;
+    registry.register_code_hash("Wallet", "AB01").unwrap();
+    let matching = registry.matching(&body_with_id(0x315ef935), MessageType::Internal, Some("ab01")).unwrap();
+    assert_eq!(matching.len(), 1);
+    assert_eq!(matching[0].0, "Wallet");
+    assert_eq!(registry.abi_by_code_hash("ab01").unwrap().0, "Wallet");
+    assert!(registry.matching(&body_with_id(0x315ef935), MessageType::Internal, Some("ff")).is_err());
+    assert!(registry.register_code_hash("Unknown", "ff").is_err());
+}
+
+#[test]
+fn test_registry_external_header() {
+    let registry = registry();
+
+    // multisig message body: signature flag, pubkey, time and expire precede function id
+    let mut body = CellBuilder::new();
+    body.append_bit(false).unwrap();
+    body.append_bit(true).unwrap();
+    body.append_raw(&[0x22; 32], 256).unwrap();
+    body.append_u64(1600000000000).unwrap();
+    body.append_u32(1600000060).unwrap();
+    body.append_u32(0x73122f72).unwrap();
+
+    let matching = registry.matching(&body.build().unwrap(), MessageType::ExternalIn, None).unwrap();
+    assert_eq!(matching, vec![("Multisig".to_owned(), "getTransactions".to_owned(), MessageBodyType::Input)]);
+}
@@ -0,0 +1,166 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::{Cell, CellBuilder, Grams, MessageBuilder};
+
+#[test]
+fn test_boc_serialization() {
+    let elector: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string("src/tests/contracts/elector.json").unwrap()).unwrap();
+    let elector_data = base64::decode(elector["data"].as_str().unwrap()).unwrap();
+
+    for boc in [&WALLET_IMAGE[..], &SUBSCRIBE_IMAGE[..], &elector_data[..]].iter() {
+        let cell = Cell::from_boc(boc).unwrap();
+        let serialized = cell.to_boc().unwrap();
+
+        assert_eq!(Cell::from_boc(&serialized).unwrap(), cell);
+    }
+
+    let mut corrupted = elector_data.clone();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 1;
+    assert!(Cell::from_boc(&corrupted).is_err());
+}
+
+#[test]
+fn test_boc_format() {
+    // empty cell serialized by core without index and CRC
+    let empty = Cell::from_boc(&base64::decode("te6ccgEBAQEAAgAAAA==").unwrap()).unwrap();
+    assert_eq!(empty, Cell::default());
+    assert_eq!(empty.bit_len(), 0);
+    assert!(empty.references().is_empty());
+
+    // all bundled contract images survive serialization round trip
+    for version in &["abi_v1", "abi_v2"] {
+        for name in &["Hello", "LimitWallet", "Piggy", "Subscription", "Wallet"] {
+            let image = std::fs::read(format!("{}{}/{}.tvc", ROOT_CONTRACTS_PATH, version, name)).unwrap();
+            let cell = Cell::from_boc(&image).unwrap();
+            let serialized = cell.to_boc().unwrap();
+            assert_eq!(Cell::from_boc(&serialized).unwrap(), cell, "{}/{}", version, name);
+            assert_eq!(Cell::from_boc(&serialized).unwrap().to_boc().unwrap(), serialized, "{}/{}", version, name);
+
+            // any truncated BOC is rejected
+            for len in 0..image.len() {
+                assert!(Cell::from_boc(&image[..len]).is_err(), "{}/{} truncated to {}", version, name, len);
+            }
+        }
+    }
+
+    // same cell referenced twice is stored once
+    let child = CellBuilder::new().append_u32(1).unwrap().build().unwrap();
+    let root = CellBuilder::new()
+        .append_reference(child.clone()).unwrap()
+        .append_reference(child).unwrap()
+        .build().unwrap();
+    let boc = root.to_boc().unwrap();
+    // tag, flags, offset size and cells count
+    assert_eq!(boc[6], 2);
+    assert_eq!(Cell::from_boc(&boc).unwrap(), root);
+
+    let mut unknown_tag = boc.clone();
+    unknown_tag[0] = 0;
+    assert!(Cell::from_boc(&unknown_tag).is_err());
+}
+
+#[test]
+fn test_cell_builder() {
+    let address = TonAddress::from_str(
+        "-1:fcb91a3a3816d0f7b8c2c76108b8a9bc5a6b7a55bd79f8ab101c52db29232260").unwrap();
+    let var_address = TonAddress::Var(100, vec![1, 2, 3]);
+
+    let child = CellBuilder::new().append_u32(0xdeadbeef).unwrap().build().unwrap();
+    let mut builder = CellBuilder::new();
+    builder
+        .append_bit(true).unwrap()
+        .append_bits(0b101, 3).unwrap()
+        .append_grams(Grams(1_500_000_000)).unwrap()
+        .append_address(Some(&address)).unwrap()
+        .append_address(Some(&var_address)).unwrap()
+        .append_address(None).unwrap()
+        .append_u64(u64::MAX).unwrap()
+        .append_reference(child.clone()).unwrap();
+    let cell = builder.build().unwrap();
+
+    let mut slice = cell.slice();
+    assert!(slice.get_bit().unwrap());
+    assert_eq!(slice.get_bits(3).unwrap(), 0b101);
+    assert_eq!(slice.get_grams().unwrap(), Grams(1_500_000_000));
+    assert_eq!(slice.get_address().unwrap(), Some(address));
+    assert_eq!(slice.get_address().unwrap(), Some(var_address));
+    assert_eq!(slice.get_address().unwrap(), None);
+    assert_eq!(slice.get_u64().unwrap(), u64::MAX);
+    assert_eq!(slice.get_reference().unwrap(), child);
+    assert!(slice.is_empty());
+    assert!(slice.get_bit().is_err());
+
+    let mut builder = CellBuilder::new();
+    assert!(builder.append_bytes(&[0u8; 128]).is_err());
+    for _ in 0..4 {
+        builder.append_reference(Cell::default()).unwrap();
+    }
+    assert!(builder.append_reference(Cell::default()).is_err());
+}
+
+#[test]
+fn test_internal_message_builder() {
+    let src = TonAddress::from_str(
+        "0:1111111111111111111111111111111111111111111111111111111111111111").unwrap();
+    let dst = TonAddress::from_str(
+        "0:2222222222222222222222222222222222222222222222222222222222222222").unwrap();
+    let body = CellBuilder::new().append_u32(0x12345678).unwrap().build().unwrap();
+
+    let message = MessageBuilder::internal(Some(src.clone()), dst.clone(), Grams(1_000))
+        .bounce(false)
+        .created(10, 20)
+        .state_init(&HELLO_IMAGE).unwrap()
+        .body(body.clone())
+        .build_boc().unwrap();
+
+    let mut slice = Cell::from_boc(&message).unwrap().slice();
+    assert_eq!(slice.get_bits(4).unwrap(), 0b0100);         // int_msg_info, ihr_disabled, !bounce, !bounced
+    assert_eq!(slice.get_address().unwrap(), Some(src));
+    assert_eq!(slice.get_address().unwrap(), Some(dst.clone()));
+    assert_eq!(slice.get_grams().unwrap(), Grams(1_000));
+    assert!(!slice.get_bit().unwrap());
+    assert_eq!(slice.get_grams().unwrap(), Grams::zero());
+    assert_eq!(slice.get_grams().unwrap(), Grams::zero());
+    assert_eq!(slice.get_u64().unwrap(), 10);
+    assert_eq!(slice.get_u32().unwrap(), 20);
+    assert_eq!(slice.get_bits(2).unwrap(), 0b11);
+    assert_eq!(slice.get_reference().unwrap(), Cell::from_boc(&HELLO_IMAGE).unwrap());
+    assert!(slice.get_bit().unwrap());
+    assert_eq!(slice.get_reference().unwrap(), body);
+    assert!(slice.is_empty());
+
+    let message = MessageBuilder::external(dst.clone()).build_boc().unwrap();
+    let mut slice = Cell::from_boc(&message).unwrap().slice();
+    assert_eq!(slice.get_bits(2).unwrap(), 0b10);
+    assert_eq!(slice.get_address().unwrap(), None);
+    assert_eq!(slice.get_address().unwrap(), Some(dst));
+    assert_eq!(slice.get_grams().unwrap(), Grams::zero());
+    assert_eq!(slice.get_bits(2).unwrap(), 0b00);
+    assert!(slice.is_empty());
+}
+
+#[test]
+fn test_internal_message_local_run() {
+    let ton = create_client();
+
+    let keys = ton.crypto.generate_ed25519_keys().unwrap();
+    let address = deploy_with_giver(
+        &ton, HELLO_ABI.to_string().into(), &HELLO_IMAGE, json!({}).into(), &keys);
+
+    let message = MessageBuilder::internal(Some(WALLET_ADDRESS.clone()), address.clone(), Grams(100_000_000))
+        .body_from_abi(&ton.contracts, HELLO_ABI.to_string().into(), "sayHello", None, json!({}).into(), None)
+        .unwrap()
+        .build(&ton.contracts)
+        .unwrap();
+
+    let result = ton.contracts.run_local_msg(
+        &address, None, message, None, None, None, true).unwrap();
+
+    assert!(result.fees.is_some());
+    assert!(result.account.is_some());
+}
@@ -0,0 +1,35 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::LocalClock;
+
+#[test]
+fn test_local_clock() {
+    let mut clock = LocalClock::new(1_600_000_000).with_lt(2_500_000).with_lt_step(10);
+    assert_eq!(clock.block_lt(), 2_000_000);
+
+    let context = clock.next_context();
+    assert_eq!(context.time, Some(1_600_000_000));
+    assert_eq!(context.transaction_lt, Some(2_500_010));
+    assert_eq!(context.block_lt, Some(2_000_000));
+    assert_eq!(clock.next_context().transaction_lt, Some(2_500_020));
+
+    // read-only context doesn't advance clock
+    assert_eq!(clock.context().transaction_lt, Some(2_500_020));
+
+    clock.advance(3600);
+    let context = clock.next_context();
+    assert_eq!(context.time, Some(1_600_003_600));
+    assert_eq!(context.block_lt, Some(3_000_000));
+    assert_eq!(context.transaction_lt, Some(3_000_010));
+
+    assert!(clock.set_time(1_600_000_000).is_err());
+    clock.set_time(1_700_000_000).unwrap();
+    assert_eq!(clock.time(), 1_700_000_000);
+    assert_eq!(clock.block_lt(), 4_000_000);
+
+    let history = clock.history();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[2].time, Some(1_600_003_600));
+}
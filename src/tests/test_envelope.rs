@@ -0,0 +1,69 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::{abi_hash, EncodedMessage, EnvelopeFormat, MessageEnvelope, MESSAGE_ENVELOPE_VERSION};
+use sha2::Digest;
+
+fn test_message() -> EncodedMessage {
+    EncodedMessage {
+        message_id: "5f6b2e1e7cd4a0b57f1d29cbb0b8b4c8c1a9d4a7b1f2e3d4c5b6a79881726354".to_owned(),
+        message_body: vec![0xb5, 0xee, 0x9c, 0x72, 1, 2, 3],
+        expire: Some(1_600_000_000),
+        address: WALLET_ADDRESS.clone(),
+    }
+}
+
+#[test]
+fn test_envelope_formats() {
+    let abi: JsonValue = WALLET_ABI.to_string().into();
+    let envelope = MessageEnvelope::new(test_message(), Some(&abi), Some("createOperationLimit")).unwrap();
+
+    assert_eq!(envelope.version, MESSAGE_ENVELOPE_VERSION);
+    assert!(envelope.matches_abi(&abi).unwrap());
+    assert!(!envelope.matches_abi(&HELLO_ABI.to_string().into()).unwrap());
+    // ABI hash doesn't depend on formatting
+    let reformatted: serde_json::Value = serde_json::from_str(&WALLET_ABI).unwrap();
+    assert!(envelope.matches_abi(&reformatted.into()).unwrap());
+    // ...and on keys order
+    assert_eq!(
+        abi_hash(&json!({ "b": 1, "a": { "d": [1, "x"], "c": null } }).into()).unwrap(),
+        abi_hash(&r#"{"a":{"c":null,"d":[1,"x"]},"b":1}"#.to_string().into()).unwrap());
+    assert_eq!(
+        abi_hash(&json!({ "b": 1, "a": 2 }).into()).unwrap(),
+        hex::encode(sha2::Sha256::digest(br#"{"a":2,"b":1}"#)));
+
+    let json = envelope.to_json().unwrap();
+    assert_eq!(MessageEnvelope::from_json(&json).unwrap(), envelope);
+
+    let bytes = envelope.to_bytes();
+    assert_eq!(MessageEnvelope::from_bytes(&bytes).unwrap(), envelope);
+    assert!(MessageEnvelope::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+    let envelope = MessageEnvelope::new(test_message(), None, None).unwrap();
+    assert_eq!(MessageEnvelope::from_bytes(&envelope.to_bytes()).unwrap(), envelope);
+
+    let mut value: serde_json::Value = serde_json::from_str(&envelope.to_json().unwrap()).unwrap();
+    value["version"] = json!(MESSAGE_ENVELOPE_VERSION + 1);
+    assert!(MessageEnvelope::from_json(&value.to_string()).is_err());
+}
+
+#[test]
+fn test_envelope_files() {
+    let dir = std::env::temp_dir();
+    let abi: JsonValue = WALLET_ABI.to_string().into();
+
+    for (format, name) in [(EnvelopeFormat::Json, "message.json"), (EnvelopeFormat::Binary, "message.boc")].iter() {
+        let path = dir.join(format!("ton-client-rs-test-{}-{}", std::process::id(), name));
+
+        test_message().to_file(&path, Some(&abi), Some("createOperationLimit"), *format).unwrap();
+
+        assert_eq!(EncodedMessage::from_file(&path).unwrap(), test_message());
+        let envelope = MessageEnvelope::load(&path).unwrap();
+        assert_eq!(envelope.function_name.as_deref(), Some("createOperationLimit"));
+        assert!(envelope.matches_abi(&abi).unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,103 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::{AbiInfo, CellBuilder, SAFE_MULTISIG_ABI};
+use crate::events::{abi_event_names, order_by_ids};
+
+#[test]
+fn test_abi_event_names() {
+    let abi: serde_json::Value = serde_json::from_str(SAFE_MULTISIG_ABI).unwrap();
+    assert_eq!(abi_event_names(&abi), vec!["TransferAccepted".to_owned()]);
+
+    let abi: serde_json::Value = serde_json::from_str(&HELLO_ABI).unwrap();
+    assert!(abi_event_names(&abi).is_empty());
+}
+
+#[test]
+fn test_decode_events_skips_non_events() {
+    let ton = TonClient::default().unwrap();
+
+    let transaction = json!({
+        "id": "1",
+        "out_messages": [
+            { "id": "2", "msg_type": 0, "body": "te6ccgEBAQEAAgAAAA==" },
+            { "id": "3", "msg_type": 2 }
+        ]
+    });
+
+    let events = ton.contracts.decode_events(SAFE_MULTISIG_ABI.into(), &transaction).unwrap();
+    assert!(events.is_empty());
+
+    // ABI without events doesn't require messages at all
+    let events = ton.contracts.decode_events(HELLO_ABI.to_string().into(), &json!({})).unwrap();
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_decode_events() {
+    let ton = TonClient::default().unwrap();
+
+    let abi = AbiInfo::parse(SAFE_MULTISIG_ABI.into()).unwrap();
+    let event_body = |payload: &[u8]| {
+        let payload = CellBuilder::new().append_bytes(payload).unwrap().build().unwrap();
+        let body = CellBuilder::new()
+            .append_u32(abi.event("TransferAccepted").unwrap().id).unwrap()
+            .append_reference(payload).unwrap()
+            .build().unwrap();
+        base64::encode(&body.to_boc().unwrap())
+    };
+
+    let transaction = json!({
+        "id": "1",
+        "out_messages": [
+            { "id": "2", "msg_type": 2, "body": event_body(b"hello") },
+            // body not matching ABI is skipped
+            { "id": "3", "msg_type": 2, "body": "te6ccgEBAQEABgAACAAAAAE=" },
+            { "id": "4", "msg_type": 2, "body": event_body(b"world") }
+        ]
+    });
+
+    let events = ton.contracts.decode_events(SAFE_MULTISIG_ABI.into(), &transaction).unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].name, "TransferAccepted");
+    assert_eq!(events[0].params, json!({ "payload": hex::encode("hello") }));
+    assert_eq!(events[0].message_id, "2");
+    assert_eq!(events[1].params, json!({ "payload": hex::encode("world") }));
+    assert_eq!(events[1].message_id, "4");
+}
+
+#[test]
+fn test_order_by_ids() {
+    let ids = vec![json!("a"), json!("b"), json!("c")];
+    let messages = vec![json!({ "id": "c" }), json!({ "id": "a" }), json!({ "id": "b" })];
+
+    let ordered = order_by_ids(messages, &ids);
+    assert_eq!(ordered, vec![json!({ "id": "a" }), json!({ "id": "b" }), json!({ "id": "c" })]);
+}
+
+#[test]
+fn test_seen_messages() {
+    let mut seen = crate::events::SeenMessages::new(2);
+
+    assert!(seen.insert(&json!({ "id": "1", "created_at": 100 })));
+    assert!(!seen.insert(&json!({ "id": "1", "created_at": 100 })));
+    assert!(seen.insert(&json!({ "id": "2", "created_at": 90 })));
+    assert_eq!(seen.last_created_at, Some(100));
+
+    // the oldest id is forgotten when capacity is exceeded
+    assert!(seen.insert(&json!({ "id": "3", "created_at": 110 })));
+    assert!(seen.insert(&json!({ "id": "1", "created_at": 100 })));
+    assert!(!seen.insert(&json!({ "id": "3", "created_at": 110 })));
+    assert_eq!(seen.last_created_at, Some(110));
+}
+
+#[test]
+fn test_subscribe_unknown_event() {
+    let ton = TonClient::default().unwrap();
+
+    let result = ton.contracts.subscribe_events(
+        &WALLET_ADDRESS, SAFE_MULTISIG_ABI.into(), Some(&["Unknown"]));
+    assert!(result.is_err());
+}
@@ -0,0 +1,64 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::Grams;
+use std::convert::TryFrom;
+
+#[test]
+fn test_grams_parsing() {
+    assert_eq!("1500000000".parse::<Grams>().unwrap(), Grams(1_500_000_000));
+    assert_eq!("0x36b1f820a400".parse::<Grams>().unwrap(), Grams(0x36b1f820a400));
+    assert_eq!(Grams::from_tokens_str("1.5").unwrap(), Grams(1_500_000_000));
+    assert_eq!(Grams::from_tokens_str("0.000000001").unwrap(), Grams(1));
+    assert_eq!(Grams::from_tokens_str(".25").unwrap(), Grams(250_000_000));
+    assert_eq!(Grams::from_tokens_str("12").unwrap(), Grams::from_tokens(12).unwrap());
+
+    assert!(Grams::from_tokens_str("0.0000000001").is_err());
+    assert!(Grams::from_tokens_str("1,5").is_err());
+    assert!(Grams::from_tokens_str("-1").is_err());
+    assert!(Grams::from_tokens_str(".").is_err());
+    assert!("0xzz".parse::<Grams>().is_err());
+    assert!("+100".parse::<Grams>().is_err());
+    assert!("0x+ff".parse::<Grams>().is_err());
+}
+
+#[test]
+fn test_grams_formatting() {
+    let grams = Grams(1_234_567_890);
+
+    assert_eq!(grams.to_string(), "1234567890");
+    assert_eq!(grams.to_tokens_string(0), "1");
+    assert_eq!(grams.to_tokens_string(2), "1.23");
+    assert_eq!(grams.to_tokens_string(9), "1.234567890");
+    assert_eq!(grams.to_tokens_string(11), "1.23456789000");
+    assert_eq!(Grams(5).to_tokens_string(9), "0.000000005");
+}
+
+#[test]
+fn test_grams_arithmetic() {
+    let a = Grams(10);
+    let b = Grams(3);
+
+    assert_eq!(a.checked_add(b), Some(Grams(13)));
+    assert_eq!(a.checked_sub(b), Some(Grams(7)));
+    assert_eq!(b.checked_sub(a), None);
+    assert_eq!(a.checked_mul(3), Some(Grams(30)));
+    assert_eq!(a.checked_div(3), Some(Grams(3)));
+    assert_eq!(a.checked_div(0), None);
+    assert_eq!(Grams(u128::MAX).checked_add(Grams(1)), None);
+
+    assert_eq!(u64::try_from(Grams(42)).unwrap(), 42);
+    assert!(u64::try_from(Grams(u64::MAX as u128 + 1)).is_err());
+}
+
+#[test]
+fn test_grams_serde() {
+    let grams = Grams(5_000_000_000);
+
+    assert_eq!(serde_json::to_value(grams).unwrap(), json!("5000000000"));
+    assert_eq!(serde_json::from_value::<Grams>(json!("5000000000")).unwrap(), grams);
+    assert_eq!(serde_json::from_value::<Grams>(json!("0x12a05f200")).unwrap(), grams);
+    assert_eq!(serde_json::from_value::<Grams>(json!(5_000_000_000u64)).unwrap(), grams);
+    assert!(serde_json::from_value::<Grams>(json!(-1)).is_err());
+}
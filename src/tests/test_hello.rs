@@ -55,3 +55,102 @@ fn test_hello() {
 
     println!("Hello contract was responded to sayHello: {:#?}", response);
 }
+
+#[test]
+fn test_hello_with_events() {
+    let ton_client = create_client();
+
+    let keypair = ton_client.crypto.generate_ed25519_keys().expect("Couldn't create key pair");
+
+    let prepared_address = ton_client.contracts.get_deploy_address(
+        HELLO_ABI.to_string().into(),
+        &HELLO_IMAGE,
+        None,
+        &keypair.public,
+        0).expect("Couldn't get deploy address");
+
+    super::get_grams_from_giver(&ton_client, &prepared_address, None);
+
+    let events = std::sync::Mutex::new(vec![]);
+    let on_event = |event: &crate::ProcessingEvent| events.lock().unwrap().push(event.clone());
+
+    let params = crate::DeployParams::new(HELLO_ABI.to_string().into(), &HELLO_IMAGE, json!({}).into());
+    let hello_address = ton_client.contracts.deploy_with_events(&params, &keypair, &on_event)
+        .expect("Couldn't deploy contract")
+        .address;
+    assert_eq!(hello_address, prepared_address);
+
+    let message = ton_client.contracts.create_run_message(
+        &hello_address,
+        HELLO_ABI.to_string().into(),
+        "touch",
+        None,
+        json!({}).to_string().into(),
+        Some(&keypair),
+        None)
+        .expect("Couldn't create message");
+
+    ton_client.contracts.process_message_with_events(
+        message, Some(HELLO_ABI.to_string().into()), Some("touch"), false, &on_event)
+        .expect("Couldn't run contract");
+
+    let events = events.into_inner().unwrap();
+    let kinds: Vec<&str> = events.iter()
+        .map(|event| match event {
+            crate::ProcessingEvent::MessageEncoded { .. } => "encoded",
+            crate::ProcessingEvent::MessageSent { .. } => "sent",
+            crate::ProcessingEvent::BlockFetched { .. } => "block",
+            crate::ProcessingEvent::TransactionReceived { .. } => "transaction",
+            crate::ProcessingEvent::Retry { .. } => "retry",
+            crate::ProcessingEvent::Expired { .. } => "expired",
+        })
+        .collect();
+    // blocks are fetched until the one with the message
+    assert!(kinds.contains(&"block"));
+    let kinds: Vec<&str> = kinds.into_iter().filter(|kind| *kind != "block").collect();
+    assert_eq!(kinds, vec!["encoded", "sent", "transaction", "sent", "transaction"]);
+}
+
+#[test]
+fn test_deploy_many() {
+    let ton_client = create_client();
+
+    assert!(ton_client.contracts.deploy_many(vec![], 4).is_empty());
+
+    let items: Vec<crate::DeployItem> = (0..3)
+        .map(|_| {
+            let keys = ton_client.crypto.generate_ed25519_keys().expect("Couldn't create key pair");
+            crate::DeployItem {
+                params: crate::DeployParams::new(HELLO_ABI.to_string().into(), &HELLO_IMAGE, json!({}).into()),
+                keys,
+            }
+        })
+        .collect();
+
+    let addresses: Vec<TonAddress> = items.iter()
+        .map(|item| {
+            let address = ton_client.contracts.get_deploy_address(
+                item.params.abi.clone(), &item.params.image, None, &item.keys.public, 0)
+                .expect("Couldn't get deploy address");
+            super::get_grams_from_giver(&ton_client, &address, None);
+            address
+        })
+        .collect();
+
+    let sent = std::cell::RefCell::new(vec![]);
+    let results = ton_client.contracts.deploy_many_with_events(items, 2, &|index, event| {
+        if let crate::ProcessingEvent::MessageSent { .. } = event {
+            sent.borrow_mut().push(index);
+        }
+    });
+
+    assert_eq!(results.len(), addresses.len());
+    for (result, address) in results.into_iter().zip(addresses) {
+        let result = result.expect("Couldn't deploy contract");
+        assert_eq!(result.address, address);
+        assert!(!result.already_deployed);
+    }
+    let mut sent = sent.into_inner();
+    sent.sort();
+    assert_eq!(sent, vec![0, 1, 2]);
+}
@@ -0,0 +1,96 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{I256, U256};
+use std::convert::TryFrom;
+
+const PUBKEY: &str = "0xe31e8aac98f0032b92e37618aba8a3117a983cd957bae120de29e6fd7ceb8e9d";
+
+#[test]
+fn test_u256_parsing() {
+    let pubkey = PUBKEY.parse::<U256>().unwrap();
+    assert_eq!(format!("{:#x}", pubkey), PUBKEY);
+    assert_eq!(hex::encode(pubkey.to_be_bytes()), &PUBKEY[2..]);
+    assert_eq!(U256::from_be_bytes(pubkey.to_be_bytes()), pubkey);
+
+    let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+    assert_eq!(max.parse::<U256>().unwrap(), U256::MAX);
+    assert_eq!(U256::MAX.to_string(), max);
+    assert_eq!("5000000000".parse::<U256>().unwrap(), U256::from(5_000_000_000u64));
+    assert_eq!(U256::ZERO.to_string(), "0");
+    assert_eq!(format!("{:x}", U256::ZERO), "0");
+    assert_eq!(U256::from(10_000_000_000_000_000_000u128).to_string(), "10000000000000000000");
+
+    assert!("115792089237316195423570985008687907853269984665640564039457584007913129639936".parse::<U256>().is_err());
+    assert!("-1".parse::<U256>().is_err());
+    assert!("0xzz".parse::<U256>().is_err());
+}
+
+#[test]
+fn test_u256_arithmetic() {
+    let a = U256::from(u128::MAX);
+    let b = U256::from(3u64);
+
+    assert_eq!((a + U256::ONE).to_string(), "340282366920938463463374607431768211456");
+    assert_eq!(a * a / a, a);
+    assert_eq!(a % b, U256::ZERO);
+    assert_eq!((a - b) % b, U256::ZERO);
+    assert!(a > b && U256::MAX > a);
+
+    assert_eq!(U256::MAX.checked_add(U256::ONE), None);
+    assert_eq!(U256::ZERO.checked_sub(U256::ONE), None);
+    assert_eq!(U256::MAX.checked_mul(b), None);
+    assert_eq!(a.checked_div(U256::ZERO), None);
+
+    assert_eq!(u128::try_from(a).unwrap(), u128::MAX);
+    assert!(u64::try_from(a).is_err());
+}
+
+#[test]
+fn test_i256() {
+    let min = "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+    assert_eq!(min.parse::<I256>().unwrap(), I256::MIN);
+    assert_eq!(I256::MIN.to_string(), min);
+    assert!(min.replace("968", "969").parse::<I256>().is_err());
+    assert_eq!("-0x10".parse::<I256>().unwrap(), I256::from(-16i64));
+
+    let a = I256::from(-7i64);
+    let b = I256::from(2i64);
+    assert_eq!(a / b, I256::from(-3i64));
+    assert_eq!(a % b, I256::from(-1i64));
+    assert_eq!(a * b, I256::from(-14i64));
+    assert_eq!(a - b, I256::from(-9i64));
+    assert_eq!(-a + b, I256::from(9i64));
+    assert!(a < b && I256::MIN < a && I256::MAX > b);
+
+    assert_eq!(I256::MAX.checked_add(I256::ONE), None);
+    assert_eq!(I256::MIN.checked_sub(I256::ONE), None);
+    assert_eq!(I256::MIN.checked_neg(), None);
+    assert_eq!(I256::MIN.checked_div(I256::from(-1i64)), None);
+
+    assert_eq!(i128::try_from(I256::from(i128::MIN)).unwrap(), i128::MIN);
+    assert_eq!(i64::try_from(a).unwrap(), -7);
+    assert!(i128::try_from(I256::MAX).is_err());
+    assert!(U256::try_from(a).is_err());
+    assert_eq!(I256::try_from(U256::from(7u64)).unwrap(), -a);
+}
+
+#[test]
+#[should_panic(expected = "attempt to add with overflow")]
+fn test_int256_operator_overflow() {
+    let _ = U256::MAX + U256::ONE;
+}
+
+#[test]
+fn test_int256_serde() {
+    let pubkey = PUBKEY.parse::<U256>().unwrap();
+    assert_eq!(serde_json::to_value(pubkey).unwrap(), json!(PUBKEY));
+    assert_eq!(serde_json::from_value::<U256>(json!(PUBKEY)).unwrap(), pubkey);
+    assert_eq!(serde_json::from_value::<U256>(json!(42)).unwrap(), U256::from(42u64));
+    assert!(serde_json::from_value::<U256>(json!(-1)).is_err());
+
+    assert_eq!(serde_json::to_value(I256::from(-5i64)).unwrap(), json!("-5"));
+    assert_eq!(serde_json::from_value::<I256>(json!(-5)).unwrap(), I256::from(-5i64));
+    assert_eq!(serde_json::from_value::<I256>(json!("-0x5")).unwrap(), I256::from(-5i64));
+}
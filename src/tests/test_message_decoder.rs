@@ -0,0 +1,120 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::{encode_comment, parse_message, AbiInfo, CellBuilder, Grams, MessageBodyType, MessageBuilder, MessageType,
+    SAFE_MULTISIG_ABI};
+
+#[test]
+fn test_parse_internal_message() {
+    let mut body = CellBuilder::new();
+    body.append_u32(0x12345678).unwrap();
+    let body = body.build().unwrap();
+
+    let src = TonAddress::from_str(crate::NODE_SE_GIVER_ADDRESS).unwrap();
+    let message = MessageBuilder::internal(Some(src.clone()), WALLET_ADDRESS.clone(), Grams(1_000_000))
+        .bounce(false)
+        .created(12345, 1600000000)
+        .state_init(&HELLO_IMAGE)
+        .unwrap()
+        .body(body.clone())
+        .build_boc()
+        .unwrap();
+
+    let parsed = parse_message(&message).unwrap();
+    assert_eq!(parsed.message_type, MessageType::Internal);
+    assert_eq!(parsed.src.as_ref(), Some(&src));
+    assert_eq!(parsed.dst.as_ref(), Some(&*WALLET_ADDRESS));
+    assert_eq!(parsed.value, Some(Grams(1_000_000)));
+    assert!(!parsed.bounce);
+    assert!(!parsed.bounced);
+    assert_eq!(parsed.created_lt, 12345);
+    assert_eq!(parsed.created_at, 1600000000);
+    assert_eq!(parsed.state_init.unwrap().to_boc().unwrap(), crate::Cell::from_boc(&HELLO_IMAGE).unwrap().to_boc().unwrap());
+    assert_eq!(parsed.body, Some(body));
+}
+
+#[test]
+fn test_parse_external_messages() {
+    let message = MessageBuilder::external(WALLET_ADDRESS.clone()).build_boc().unwrap();
+    let parsed = parse_message(&message).unwrap();
+    assert_eq!(parsed.message_type, MessageType::ExternalIn);
+    assert_eq!(parsed.src, None);
+    assert_eq!(parsed.dst.as_ref(), Some(&*WALLET_ADDRESS));
+    assert_eq!(parsed.value, None);
+    assert_eq!(parsed.state_init, None);
+    assert_eq!(parsed.body, None);
+
+    // external outbound message with inline body
+    let mut builder = CellBuilder::new();
+    builder.append_bits(0b11, 2).unwrap();
+    builder.append_address(Some(&WALLET_ADDRESS)).unwrap();
+    builder.append_address(None).unwrap();
+    builder.append_u64(777).unwrap();
+    builder.append_u32(1600000000).unwrap();
+    builder.append_bit(false).unwrap();         // no state init
+    builder.append_bit(false).unwrap();         // inline body
+    builder.append_u32(0xdeadbeef).unwrap();
+    let parsed = parse_message(&builder.build().unwrap().to_boc().unwrap()).unwrap();
+
+    assert_eq!(parsed.message_type, MessageType::ExternalOut);
+    assert_eq!(parsed.src.as_ref(), Some(&*WALLET_ADDRESS));
+    assert_eq!(parsed.dst, None);
+    assert_eq!(parsed.created_lt, 777);
+    assert_eq!(parsed.body.unwrap().slice().get_u32().unwrap(), 0xdeadbeef);
+}
+
+#[test]
+fn test_decode_message() {
+    let ton = TonClient::default().unwrap();
+    let keys = ton.crypto.generate_ed25519_keys().unwrap();
+
+    let message = ton.contracts.create_deploy_message(
+        HELLO_ABI.to_string().into(),
+        &HELLO_IMAGE,
+        None,
+        json!({}).into(),
+        None,
+        &keys,
+        0,
+        None).unwrap();
+
+    let decoded = ton.contracts.decode_message(HELLO_ABI.to_string().into(), &message.message_body).unwrap();
+    assert_eq!(decoded.message.message_type, MessageType::ExternalIn);
+    assert_eq!(decoded.message.dst, Some(message.address));
+    assert!(decoded.message.state_init.is_some());
+
+    let body = decoded.body.unwrap();
+    assert_eq!(body.body_type, MessageBodyType::Input);
+    assert_eq!(body.name, "constructor");
+}
+
+#[test]
+fn test_decode_internal_message() {
+    let ton = TonClient::default().unwrap();
+    let abi = AbiInfo::parse(SAFE_MULTISIG_ABI.into()).unwrap();
+    let internal = |body| MessageBuilder::internal(None, WALLET_ADDRESS.clone(), Grams(1_000_000))
+        .body(body)
+        .build_boc()
+        .unwrap();
+
+    // response of internal call
+    let mut body = CellBuilder::new();
+    body.append_u32(abi.function("getParameters").unwrap().output_id).unwrap()
+        .append_u8(5).unwrap()
+        .append_u8(32).unwrap()
+        .append_u64(3600).unwrap()
+        .append_bits(1_000_000, 128).unwrap()
+        .append_u8(2).unwrap();
+    let decoded = ton.contracts.decode_message(SAFE_MULTISIG_ABI.into(), &internal(body.build().unwrap())).unwrap();
+    let body = decoded.body.unwrap();
+    assert_eq!(body.body_type, MessageBodyType::Output);
+    assert_eq!(body.name, "getParameters");
+
+    // plain transfer with comment doesn't match ABI
+    let comment = encode_comment("hello").unwrap();
+    let decoded = ton.contracts.decode_message(SAFE_MULTISIG_ABI.into(), &internal(comment.clone())).unwrap();
+    assert_eq!(decoded.body.unwrap().body_type, MessageBodyType::Unknown);
+    assert_eq!(decoded.message.body, Some(comment));
+}
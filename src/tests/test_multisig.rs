@@ -0,0 +1,183 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::{AbiInfo, Cell, DeployParams, Grams, MultisigTransaction, MultisigWallet, Sandbox, Transfer,
+    SAFE_MULTISIG_ABI, SETCODE_MULTISIG_ABI};
+use crate::multisig::{parse_custodians, parse_parameters, submit_transaction_input};
+
+#[test]
+fn test_multisig_abi() {
+    let abi: serde_json::Value = serde_json::from_str(SAFE_MULTISIG_ABI).unwrap();
+    let functions: Vec<&str> = abi["functions"].as_array().unwrap()
+        .iter()
+        .map(|function| function["name"].as_str().unwrap())
+        .collect();
+
+    for name in &["submitTransaction", "confirmTransaction", "getTransactions", "getCustodians", "getParameters"] {
+        assert!(functions.contains(name), "{} not found", name);
+    }
+
+    // Setcode ABI is a superset of Safe one
+    let setcode = AbiInfo::parse(SETCODE_MULTISIG_ABI.into()).unwrap();
+    for name in functions.iter().chain(&["submitUpdate", "confirmUpdate", "executeUpdate", "getUpdateRequests"]) {
+        assert!(setcode.function(name).is_some(), "{} not found", name);
+    }
+
+    let wallet = MultisigWallet::new(TonAddress::from_str(
+        "0:2bb4a0e8391e7ea8877f4825064924bd41ce110fce97e939d3323999e1efbb13").unwrap());
+    assert_eq!(wallet.abi(), &SAFE_MULTISIG_ABI.into());
+}
+
+#[test]
+fn test_multisig_transaction_parsing() {
+    let transaction = MultisigTransaction::from_json(&json!({
+        "id": "0x5f9a6b2c00000001",
+        "confirmationsMask": "0x5",
+        "signsRequired": "0x3",
+        "signsReceived": "0x2",
+        "creator": "0x1a2b",
+        "index": "0x0",
+        "dest": "0:2bb4a0e8391e7ea8877f4825064924bd41ce110fce97e939d3323999e1efbb13",
+        "value": "1500000000",
+        "sendFlags": "0x3",
+        "payload": "te6ccgEBAQEAAgAAAA==",
+        "bounce": false
+    })).unwrap();
+
+    assert_eq!(transaction.id, 0x5f9a6b2c00000001);
+    assert_eq!(transaction.value, Grams::from_tokens_str("1.5").unwrap());
+    assert_eq!(transaction.creator, format!("{:0>64}", "1a2b"));
+    assert_eq!(transaction.confirmations_left(), 1);
+    assert!(transaction.is_confirmed_by(0));
+    assert!(!transaction.is_confirmed_by(1));
+    assert!(transaction.is_confirmed_by(2));
+    assert!(!transaction.is_confirmed_by(40));
+
+    assert!(MultisigTransaction::from_json(&json!({ "id": "0x1" })).is_err());
+}
+
+#[test]
+fn test_multisig_integer_range() {
+    let custodians = |index: serde_json::Value| parse_custodians(&json!({
+        "custodians": [{ "index": index, "pubkey": "0x1" }]
+    }));
+
+    assert_eq!(custodians(json!("255")).unwrap()[0].index, 255);
+    assert_eq!(custodians(json!(7)).unwrap()[0].index, 7);
+    // values not fitting into field type are not truncated
+    assert!(custodians(json!("0x100")).is_err());
+    assert!(custodians(json!("-1")).is_err());
+    assert!(custodians(json!(format!("0x1{}", "0".repeat(40)))).is_err());
+    assert!(custodians(json!("abc")).is_err());
+}
+
+#[test]
+fn test_multisig_submit_input() {
+    let abi = AbiInfo::parse(SAFE_MULTISIG_ABI.into()).unwrap();
+    let dest = TonAddress::from_str(
+        "0:2bb4a0e8391e7ea8877f4825064924bd41ce110fce97e939d3323999e1efbb13").unwrap();
+    let payload = crate::encode_comment("hello").unwrap();
+
+    let input = submit_transaction_input(&Transfer::new(dest.clone(), Grams::from_tokens(2).unwrap()).payload(payload.clone())).unwrap();
+    let input = abi.function("submitTransaction").unwrap().coerce_input(&input).unwrap();
+    assert_eq!(input["dest"], dest.to_string());
+    assert_eq!(input["value"], "2000000000");
+    assert_eq!(
+        Cell::from_boc(&base64::decode(input["payload"].as_str().unwrap()).unwrap()).unwrap(),
+        payload);
+
+    // empty cell payload by default
+    let input = submit_transaction_input(&Transfer::new(dest, Grams::from(1u64)).bounce(false).all_balance(true)).unwrap();
+    assert!(abi.function("submitTransaction").unwrap().coerce_input(&input).is_ok());
+    assert_eq!(input["payload"], base64::encode(&Cell::default().to_boc().unwrap()));
+}
+
+#[test]
+fn test_multisig_getters_parsing() {
+    let custodians = parse_custodians(&json!({
+        "custodians": [
+            { "index": "0x0", "pubkey": "0xABCD" },
+            { "index": "0x1", "pubkey": "0x0123" }
+        ]
+    })).unwrap();
+    assert_eq!(custodians.len(), 2);
+    assert_eq!(custodians[1].index, 1);
+    assert_eq!(custodians[0].pubkey, format!("{:0>64}", "abcd"));
+
+    let parameters = parse_parameters(&json!({
+        "maxQueuedTransactions": "0x5",
+        "maxCustodianCount": "0x20",
+        "expirationTime": "0xe10",
+        "minValue": "0x3b9aca00",
+        "requiredTxnConfirms": "0x2"
+    })).unwrap();
+    assert_eq!(parameters.max_custodian_count, 32);
+    assert_eq!(parameters.expiration_time, 3600);
+    assert_eq!(parameters.min_value, Grams::from_tokens(1).unwrap());
+    assert_eq!(parameters.required_txn_confirms, 2);
+}
+
+fn test_multisig_local(name: &str, abi: &str, image: &Option<Vec<u8>>) {
+    let image = match image {
+        Some(image) => image,
+        None => {
+            eprintln!("{} image not found in src/contracts, test skipped", name);
+            return;
+        }
+    };
+    let ton_client = crate::TonClient::default().unwrap();
+    let keys = ton_client.crypto.generate_ed25519_keys().unwrap();
+    let other = ton_client.crypto.generate_ed25519_keys().unwrap();
+    let abi: JsonValue = abi.to_string().into();
+
+    let mut sandbox = Sandbox::new(&ton_client.contracts);
+    let address = sandbox.deploy(
+        &DeployParams::new(abi.clone(), image, json!({
+            "owners": [format!("0x{}", keys.public), format!("0x{}", other.public)],
+            "reqConfirms": 2,
+        }).into()),
+        &keys,
+        Grams::from_tokens(10).unwrap(),
+    ).unwrap();
+
+    let custodians = sandbox.run_local(&address, abi.clone(), "getCustodians", None, json!({}).into(), None).unwrap();
+    let custodians = parse_custodians(&custodians.output).unwrap();
+    assert_eq!(custodians.len(), 2);
+    // custodians are returned in pubkey order, indexes follow constructor `owners` order
+    let custodian = |keys: &crate::Ed25519KeyPair| custodians.iter()
+        .find(|custodian| custodian.pubkey == keys.public.to_string())
+        .unwrap()
+        .index;
+    assert_eq!(custodian(&keys), 0);
+    assert_eq!(custodian(&other), 1);
+
+    // two confirmations are required, so submitted transaction stays pending
+    let transfer = Transfer::new(WALLET_ADDRESS.clone(), Grams::from_tokens(1).unwrap());
+    sandbox.call(&address, abi.clone(), "submitTransaction", None, submit_transaction_input(&transfer).unwrap().into(), Some(&keys))
+        .unwrap();
+
+    let transactions = sandbox.run_local(&address, abi, "getTransactions", None, json!({}).into(), None).unwrap();
+    let transactions: Vec<MultisigTransaction> = transactions.output["transactions"].as_array().unwrap()
+        .iter()
+        .map(|transaction| MultisigTransaction::from_json(transaction).unwrap())
+        .collect();
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].dest, *WALLET_ADDRESS);
+    assert_eq!(transactions[0].value, transfer.amount);
+    assert_eq!(transactions[0].signs_required, 2);
+    assert_eq!(transactions[0].confirmations_left(), 1);
+    assert!(transactions[0].is_confirmed_by(0));
+    assert!(!transactions[0].is_confirmed_by(1));
+}
+
+#[test]
+fn test_safe_multisig_local() {
+    test_multisig_local("SafeMultisigWallet", SAFE_MULTISIG_ABI, &SAFE_MULTISIG_IMAGE);
+}
+
+#[test]
+fn test_setcode_multisig_local() {
+    test_multisig_local("SetcodeMultisigWallet", SETCODE_MULTISIG_ABI, &SETCODE_MULTISIG_IMAGE);
+}
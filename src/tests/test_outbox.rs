@@ -0,0 +1,155 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::{EncodedMessage, FileOutboxStore, InnerSdkError, Outbox, OutboxStore, PendingMessage};
+use crate::{is_final_error, TonError, TonErrorKind, TonResult, MESSAGE_EXPIRED_CODE};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+#[derive(Default)]
+struct MemoryOutboxStore {
+    messages: RefCell<HashMap<String, PendingMessage>>,
+}
+
+impl OutboxStore for MemoryOutboxStore {
+    fn save(&self, message: &PendingMessage) -> TonResult<()> {
+        self.messages.borrow_mut().insert(message.message.message_id.clone(), message.clone());
+        Ok(())
+    }
+
+    fn remove(&self, message_id: &str) -> TonResult<()> {
+        self.messages.borrow_mut().remove(message_id);
+        Ok(())
+    }
+
+    fn load_all(&self) -> TonResult<Vec<PendingMessage>> {
+        Ok(self.messages.borrow().values().cloned().collect())
+    }
+}
+
+fn sdk_error(code: isize, data: serde_json::Value) -> TonError {
+    TonErrorKind::InnerSdkError(InnerSdkError {
+        core_version: String::new(),
+        source: "node".to_owned(),
+        code,
+        message: String::new(),
+        message_processing_state: None,
+        data,
+    }).into()
+}
+
+#[test]
+fn test_file_outbox_store() {
+    let dir = std::env::temp_dir().join(format!("ton-client-rs-test-outbox-{}", std::process::id()));
+    let store = FileOutboxStore::new(&dir).unwrap();
+
+    let pending = PendingMessage {
+        message: EncodedMessage {
+            message_id: "11".repeat(32),
+            message_body: vec![1, 2, 3],
+            expire: Some(1_600_000_000),
+            address: WALLET_ADDRESS.clone(),
+        },
+        processing_state: None,
+        abi: Some(serde_json::from_str(&WALLET_ABI).unwrap()),
+        function_name: Some("createOperationLimit".to_owned()),
+    };
+    store.save(&pending).unwrap();
+
+    let state = serde_json::from_value(json!({
+        "lastBlockId": "22".repeat(32),
+        "sendingTime": 1_600_000_000
+    })).unwrap();
+    let updated = PendingMessage { processing_state: Some(state), ..pending.clone() };
+    store.save(&updated).unwrap();
+
+    let loaded = store.load_all().unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].message, pending.message);
+    assert_eq!(loaded[0].function_name, pending.function_name);
+    assert!(loaded[0].processing_state.is_some());
+
+    store.remove(&pending.message.message_id).unwrap();
+    assert!(store.load_all().unwrap().is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_outbox_resume() {
+    let ton = create_client();
+    let dir = std::env::temp_dir().join(format!("ton-client-rs-test-outbox-resume-{}", std::process::id()));
+
+    let keys = ton.crypto.generate_ed25519_keys().unwrap();
+    let address = deploy_with_giver(
+        &ton, WALLET_ABI.to_string().into(), &WALLET_IMAGE, json!({}).into(), &keys);
+
+    let message = ton.contracts.create_run_message(
+        &address,
+        WALLET_ABI.to_string().into(),
+        "createOperationLimit",
+        None,
+        json!({ "value": 123 }).into(),
+        Some(&keys),
+        None,
+    ).unwrap();
+
+    {
+        // process is stopped right after message is sent
+        let outbox = Outbox::new(FileOutboxStore::new(&dir).unwrap());
+        outbox.send(
+            &ton.contracts, message.clone(), Some(WALLET_ABI.to_string().into()), Some("createOperationLimit")
+        ).unwrap();
+    }
+
+    let outbox = Outbox::new(FileOutboxStore::new(&dir).unwrap());
+    assert_eq!(outbox.pending().unwrap().len(), 1);
+
+    let outcomes = outbox.resume(&ton.contracts, false).unwrap();
+
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(outcomes[0].message_id, message.message_id);
+    assert!(outcomes[0].result.as_ref().unwrap().output["value0"].is_string());
+    assert!(outbox.pending().unwrap().is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_outbox_final_errors() {
+    assert!(is_final_error(&sdk_error(MESSAGE_EXPIRED_CODE, json!(null))));
+    assert!(is_final_error(&sdk_error(3025, json!(null))));
+    assert!(is_final_error(&sdk_error(1, json!({ "transaction_id": "11".repeat(32), "phase": "computeVm" }))));
+    assert!(is_final_error(&sdk_error(1, json!({ "original_error": { "code": MESSAGE_EXPIRED_CODE } }))));
+    // network errors are resumable
+    assert!(!is_final_error(&sdk_error(1, json!(null))));
+    assert!(!is_final_error(&TonErrorKind::NotFound.into()));
+}
+
+#[test]
+fn test_outbox_failing_send() {
+    // client without network fails to send message
+    let ton = TonClient::default().unwrap();
+    let outbox = Outbox::new(MemoryOutboxStore::default());
+    let message = EncodedMessage {
+        message_id: "11".repeat(32),
+        message_body: vec![1, 2, 3],
+        expire: None,
+        address: WALLET_ADDRESS.clone(),
+    };
+
+    assert!(outbox.process(&ton.contracts, message.clone(), None, None, false).is_err());
+
+    // message stays in store until it is sent
+    let pending = outbox.pending().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].message, message);
+    assert!(pending[0].processing_state.is_none());
+
+    let outcomes = outbox.resume(&ton.contracts, false).unwrap();
+    assert_eq!(outcomes.len(), 1);
+    assert!(outcomes[0].result.is_err());
+    assert_eq!(outbox.pending().unwrap().len(), 1);
+}
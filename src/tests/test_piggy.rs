@@ -219,4 +219,23 @@ fn test_piggy() {
     ).unwrap();
 
     println!("getSubscription answer {:#?}", subscriptions);
+
+    let subscription: GetSubscriptionOutput = subscriptions.output_as().unwrap();
+    assert_eq!(subscription.value0.to, piggy_bank_address);
+    assert_eq!(subscription.value0.value, 5000000000);
+    assert_eq!(subscription.value0.period, 86400);
+}
+
+#[derive(Deserialize)]
+struct GetSubscriptionOutput {
+    value0: Subscription,
+}
+
+#[derive(Deserialize)]
+struct Subscription {
+    to: crate::TonAddress,
+    #[serde(with = "crate::abi_serde::uint")]
+    value: u64,
+    #[serde(with = "crate::abi_serde::uint")]
+    period: u32,
 }
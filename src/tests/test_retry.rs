@@ -0,0 +1,147 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{Backoff, InnerSdkError, RetryPolicy, TonError, TonErrorKind, MESSAGE_EXPIRED_CODE};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn sdk_error(code: isize, data: serde_json::Value) -> TonError {
+    TonErrorKind::InnerSdkError(InnerSdkError {
+        core_version: String::new(),
+        source: "client".to_owned(),
+        code,
+        message: String::new(),
+        message_processing_state: None,
+        data,
+    }).into()
+}
+
+#[test]
+fn test_backoff() {
+    assert_eq!(Backoff::None.delay(5), Duration::from_millis(0));
+    assert_eq!(Backoff::Fixed(Duration::from_millis(100)).delay(3), Duration::from_millis(100));
+
+    let backoff = Backoff::Exponential {
+        initial: Duration::from_millis(100),
+        factor: 2.0,
+        max: Duration::from_millis(500),
+    };
+    assert_eq!(backoff.delay(0), Duration::from_millis(100));
+    assert_eq!(backoff.delay(2), Duration::from_millis(400));
+    assert_eq!(backoff.delay(3), Duration::from_millis(500));
+    assert_eq!(backoff.delay(200), Duration::from_millis(500));
+
+    // invalid factors don't panic
+    for factor in [-2.0, f32::NAN, f32::NEG_INFINITY].iter() {
+        let backoff = Backoff::Exponential {
+            initial: Duration::from_millis(100),
+            factor: *factor,
+            max: Duration::from_millis(500),
+        };
+        for retry in 0..4 {
+            assert!(backoff.delay(retry) <= Duration::from_millis(500));
+        }
+    }
+    let backoff = Backoff::Exponential {
+        initial: Duration::from_millis(100),
+        factor: -2.0,
+        max: Duration::from_millis(500),
+    };
+    assert_eq!(backoff.delay(1), Duration::from_millis(0));
+}
+
+#[test]
+fn test_retryable_errors() {
+    let policy = RetryPolicy::new(3);
+
+    assert!(policy.is_retryable(&sdk_error(MESSAGE_EXPIRED_CODE, json!(null))));
+    assert!(policy.is_retryable(&sdk_error(3025, json!({
+        "original_error": { "code": MESSAGE_EXPIRED_CODE }
+    }))));
+    assert!(!policy.is_retryable(&sdk_error(3025, json!(null))));
+    assert!(!policy.is_retryable(&TonErrorKind::InvalidArg("test".to_owned()).into()));
+
+    // default policy retries expired messages like core does
+    let default = RetryPolicy::default();
+    assert_eq!(default.max_attempts, crate::DEFAULT_MESSAGE_RETRIES_COUNT + 1);
+    assert!(default.is_retryable(&sdk_error(MESSAGE_EXPIRED_CODE, json!(null))));
+
+    let policy = policy.with_retry_codes(vec![3025]);
+    assert!(policy.is_retryable(&sdk_error(3025, json!(null))));
+    assert!(!policy.is_retryable(&sdk_error(MESSAGE_EXPIRED_CODE, json!(null))));
+}
+
+#[test]
+fn test_retry_execution() {
+    let retries = Arc::new(Mutex::new(vec![]));
+    let retries_copy = retries.clone();
+    let policy = RetryPolicy::new(3)
+        .on_retry(move |attempt| retries_copy.lock().unwrap().push(attempt.attempt));
+
+    // succeeds on the last attempt
+    let result = policy.execute(|attempt| {
+        if attempt < 2 {
+            Err(sdk_error(MESSAGE_EXPIRED_CODE, json!(null)))
+        } else {
+            Ok(attempt)
+        }
+    });
+    assert_eq!(result.unwrap(), 2);
+    assert_eq!(*retries.lock().unwrap(), vec![1, 2]);
+
+    // attempts are exhausted
+    let mut attempts = 0;
+    let result: crate::TonResult<()> = policy.execute(|_| {
+        attempts += 1;
+        Err(sdk_error(MESSAGE_EXPIRED_CODE, json!(null)))
+    });
+    assert!(result.is_err());
+    assert_eq!(attempts, 3);
+
+    // non-retryable error is returned immediately
+    let mut attempts = 0;
+    let result: crate::TonResult<()> = policy.execute(|_| {
+        attempts += 1;
+        Err(sdk_error(3025, json!(null)))
+    });
+    assert!(result.is_err());
+    assert_eq!(attempts, 1);
+
+    let mut attempts = 0;
+    let result: crate::TonResult<()> = RetryPolicy::no_retries().execute(|_| {
+        attempts += 1;
+        Err(sdk_error(MESSAGE_EXPIRED_CODE, json!(null)))
+    });
+    assert!(result.is_err());
+    assert_eq!(attempts, 1);
+
+    // filtered error is not retried even if its code is retryable
+    let mut attempts = 0;
+    let result: crate::TonResult<()> = policy.execute_filtered(
+        |_| {
+            attempts += 1;
+            Err(sdk_error(MESSAGE_EXPIRED_CODE, json!(null)))
+        },
+        |_| {},
+        |err| !crate::retry::has_error_code(err, MESSAGE_EXPIRED_CODE));
+    assert!(result.is_err());
+    assert_eq!(attempts, 1);
+}
+
+#[test]
+fn test_retry_notification() {
+    let notified = Mutex::new(vec![]);
+    let result = RetryPolicy::new(3)
+        .with_backoff(Backoff::Fixed(Duration::from_millis(1)))
+        .execute_with_notify(
+            |attempt| if attempt == 0 {
+                Err(sdk_error(MESSAGE_EXPIRED_CODE, json!(null)))
+            } else {
+                Ok(())
+            },
+            |retry| notified.lock().unwrap().push((retry.attempt, retry.delay)));
+
+    assert!(result.is_ok());
+    assert_eq!(notified.into_inner().unwrap(), vec![(1, Duration::from_millis(1))]);
+}
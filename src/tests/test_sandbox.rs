@@ -0,0 +1,139 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::{CellBuilder, DeployParams, Grams, LocalClock, MessageBuilder, MessageType, Sandbox};
+
+#[test]
+fn test_sandbox() {
+    let ton_client = crate::TonClient::default().unwrap();
+    let keypair = ton_client.crypto.generate_ed25519_keys().unwrap();
+    let abi: JsonValue = HELLO_ABI.to_string().into();
+
+    let mut sandbox = Sandbox::with_clock(&ton_client.contracts, LocalClock::new(now()));
+    sandbox.set_message_routing(false);
+    let address = sandbox.deploy(
+        &DeployParams::new(abi.clone(), &HELLO_IMAGE, json!({}).into()), &keypair, Grams::from_tokens(10).unwrap()
+    ).unwrap();
+
+    assert_eq!(sandbox.account(&address).unwrap().account()["acc_type"], 1);
+    let deployed_balance = sandbox.balance(&address).unwrap();
+    assert!(deployed_balance < Grams::from_tokens(10).unwrap());
+
+    let transaction = sandbox.call(&address, abi.clone(), "touch", None, json!({}).into(), Some(&keypair)).unwrap();
+    assert_eq!(transaction.function_name.as_deref(), Some("touch"));
+    assert!(transaction.fees.is_some());
+    assert!(sandbox.balance(&address).unwrap() < deployed_balance);
+
+    // contract saves transaction time in `touch` and return it in `sayHello`
+    sandbox.clock_mut().advance(3600);
+    let time = sandbox.clock().time();
+    sandbox.call(&address, abi.clone(), "touch", None, json!({}).into(), Some(&keypair)).unwrap();
+    let result = sandbox.run_local(&address, abi.clone(), "sayHello", None, json!({}).into(), None).unwrap();
+    assert_eq!(result.output["value0"], format!("{}", time));
+
+    // internal transfer to sandboxed account and to unknown one
+    let balance = sandbox.balance(&address).unwrap();
+    let unknown = TonAddress::from_str(&format!("0:{}", "1".repeat(64))).unwrap();
+    sandbox.send(&MessageBuilder::internal(None, address.clone(), Grams::from_tokens(1).unwrap()).bounce(false), None, None).unwrap();
+    sandbox.send(&MessageBuilder::internal(None, unknown, Grams::from_tokens(1).unwrap()), None, None).unwrap();
+    assert_eq!(sandbox.run_queue().unwrap(), 1);
+    assert_eq!(sandbox.undelivered().len(), 1);
+    assert!(sandbox.balance(&address).unwrap() > balance);
+
+    assert_eq!(sandbox.transactions().len(), 4);
+    assert_eq!(sandbox.transactions()[2].context.time, Some(time));
+    assert_eq!(sandbox.clock().history().len(), 4);
+    assert!(sandbox.transactions().iter().all(|transaction| transaction.address == address));
+}
+
+#[test]
+fn test_sandbox_routing() {
+    let ton_client = crate::TonClient::default().unwrap();
+    let keypair = ton_client.crypto.generate_ed25519_keys().unwrap();
+    let wallet_abi: JsonValue = SIMPLE_WALLET_ABI.to_string().into();
+
+    let mut sandbox = Sandbox::new(&ton_client.contracts);
+    let hello = sandbox.deploy(
+        &DeployParams::new(HELLO_ABI.to_string().into(), &HELLO_IMAGE, json!({}).into()), &keypair, Grams::from_tokens(1).unwrap()
+    ).unwrap();
+    let wallet = sandbox.deploy(
+        &DeployParams::new(wallet_abi.clone(), &SIMPLE_WALLET_IMAGE, json!({}).into()), &keypair, Grams::from_tokens(10).unwrap()
+    ).unwrap();
+    let unknown = TonAddress::from_str(&format!("0:{}", "1".repeat(64))).unwrap();
+    let hello_balance = sandbox.balance(&hello).unwrap();
+
+    // messages sent by contract are queued
+    for dest in &[&hello, &unknown] {
+        let transaction = sandbox.call(&wallet, wallet_abi.clone(), "sendTransaction", None, json!({
+            "dest": dest.to_string(),
+            "value": Grams::from_tokens(1).unwrap(),
+            "bounce": false,
+        }).into(), Some(&keypair)).unwrap();
+        assert_eq!(transaction.out_messages.len(), 1);
+        assert_eq!(transaction.out_messages[0].message_type, MessageType::Internal);
+        assert_eq!(transaction.out_messages[0].dst.as_ref(), Some(*dest));
+    }
+
+    assert_eq!(sandbox.run_queue().unwrap(), 1);
+    assert_eq!(sandbox.undelivered().len(), 1);
+    assert_eq!(sandbox.undelivered()[0].address, unknown);
+    assert!(sandbox.balance(&hello).unwrap() > hello_balance);
+
+    let last = sandbox.transactions().last().unwrap();
+    assert_eq!(last.address, hello);
+    assert!(last.events.is_empty());
+    assert!(sandbox.events().is_empty());
+}
+
+#[test]
+fn test_sandbox_failed_message() {
+    let ton_client = crate::TonClient::default().unwrap();
+    let keypair = ton_client.crypto.generate_ed25519_keys().unwrap();
+
+    let mut sandbox = Sandbox::new(&ton_client.contracts);
+    sandbox.set_message_routing(false);
+    let address = sandbox.deploy(
+        &DeployParams::new(HELLO_ABI.to_string().into(), &HELLO_IMAGE, json!({}).into()), &keypair, Grams::from_tokens(1).unwrap()
+    ).unwrap();
+    let balance = sandbox.balance(&address).unwrap();
+
+    // contract throws on unknown function id, the next message is processed anyway
+    let mut body = CellBuilder::new();
+    body.append_u32(0x12345678).unwrap();
+    let call = MessageBuilder::internal(None, address.clone(), Grams::from_tokens(1).unwrap())
+        .bounce(false)
+        .body(body.build().unwrap());
+    sandbox.send(&call, None, None).unwrap();
+    sandbox.send(&MessageBuilder::internal(None, address.clone(), Grams::from_tokens(1).unwrap()).bounce(false), None, None).unwrap();
+    assert_eq!(sandbox.run_queue().unwrap(), 2);
+
+    let transactions = sandbox.transactions();
+    assert_eq!(transactions.len(), 3);
+    assert!(transactions[1].error.is_some());
+    assert!(transactions[1].fees.is_none());
+    assert!(transactions[2].error.is_none());
+    assert!(sandbox.balance(&address).unwrap() > balance);
+}
+
+#[test]
+fn test_sandbox_clock_header() {
+    let ton_client = crate::TonClient::default().unwrap();
+    let keypair = ton_client.crypto.generate_ed25519_keys().unwrap();
+    let abi: JsonValue = HELLO_ABI.to_string().into();
+
+    // messages signed with system time would be expired at clock time
+    let time = now() + 24 * 3600;
+    let mut sandbox = Sandbox::with_clock(&ton_client.contracts, LocalClock::new(time));
+    sandbox.set_message_routing(false);
+    let address = sandbox.deploy(
+        &DeployParams::new(abi.clone(), &HELLO_IMAGE, json!({}).into()), &keypair, Grams::from_tokens(10).unwrap()
+    ).unwrap();
+    assert_eq!(sandbox.account(&address).unwrap().account()["acc_type"], 1);
+
+    sandbox.call(&address, abi.clone(), "touch", None, json!({}).into(), Some(&keypair)).unwrap();
+    let result = sandbox.run_local(&address, abi, "sayHello", None, json!({}).into(), None).unwrap();
+    assert_eq!(result.output["value0"], format!("{}", time));
+    assert!(sandbox.transactions().iter().all(|transaction| transaction.context.time == Some(time)));
+}
@@ -0,0 +1,73 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::{decode_comment, encode_comment, Cell, Grams, Transfer, SAFE_MULTISIG_ABI};
+use crate::transfer::transfer_input;
+
+#[test]
+fn test_comment_encoding() {
+    let comment = encode_comment("Hello").unwrap();
+    assert_eq!(comment.bit_len(), 32 + 5 * 8);
+    assert!(comment.references().is_empty());
+    assert_eq!(decode_comment(&comment).unwrap(), "Hello");
+
+    // long comment is split into chain of cells
+    let long = "Привет, TON! ".repeat(40);
+    let comment = encode_comment(&long).unwrap();
+    assert_eq!(comment.references().len(), 1);
+    assert_eq!(decode_comment(&Cell::from_boc(&comment.to_boc().unwrap()).unwrap()).unwrap(), long);
+
+    assert_eq!(decode_comment(&encode_comment("").unwrap()).unwrap(), "");
+
+    let mut not_comment = crate::CellBuilder::new();
+    not_comment.append_u32(1).unwrap();
+    assert!(decode_comment(&not_comment.build().unwrap()).is_none());
+}
+
+#[test]
+fn test_transfer_input() {
+    let amount = Grams::from_tokens(1).unwrap();
+
+    let wallet_abi: serde_json::Value = serde_json::from_str(&SIMPLE_WALLET_ABI).unwrap();
+    let transfer = Transfer::new(WALLET_ADDRESS.clone(), amount).bounce(false);
+    let (function, input) = transfer_input(&wallet_abi, &transfer).unwrap();
+    assert_eq!(function, "sendTransaction");
+    assert_eq!(input, json!({
+        "dest": WALLET_ADDRESS.to_string(),
+        "value": "1000000000",
+        "bounce": false
+    }));
+    assert!(transfer_input(&wallet_abi, &transfer.clone().comment("comment").unwrap()).is_err());
+    assert!(transfer_input(&wallet_abi, &transfer.clone().all_balance(true)).is_err());
+
+    let multisig_abi: serde_json::Value = serde_json::from_str(SAFE_MULTISIG_ABI).unwrap();
+    let commented = Transfer::new(WALLET_ADDRESS.clone(), amount).comment("comment").unwrap();
+    let (function, input) = transfer_input(&multisig_abi, &commented).unwrap();
+    assert_eq!(function, "submitTransaction");
+    assert_eq!(input["allBalance"], json!(false));
+    let payload = Cell::from_boc(&base64::decode(input["payload"].as_str().unwrap()).unwrap()).unwrap();
+    assert_eq!(decode_comment(&payload).unwrap(), "comment");
+
+    let hello_abi: serde_json::Value = serde_json::from_str(&HELLO_ABI).unwrap();
+    assert!(transfer_input(&hello_abi, &transfer).is_err());
+}
+
+#[test]
+fn test_transfer() {
+    let ton = create_client();
+    let keys = ton.crypto.generate_ed25519_keys().unwrap();
+
+    let wallet_address = deploy_with_giver(
+        &ton, SIMPLE_WALLET_ABI.to_string().into(), &SIMPLE_WALLET_IMAGE, json!({}).into(), &keys);
+
+    let result = ton.contracts.transfer(
+        &wallet_address,
+        SIMPLE_WALLET_ABI.to_string().into(),
+        &keys,
+        &Transfer::new(WALLET_ADDRESS.clone(), Grams::from(100_000_000u64)).bounce(false),
+    ).unwrap();
+
+    assert!(result.transaction["out_msgs"].as_array().map(|msgs| msgs.len()).unwrap_or(0) > 0);
+}
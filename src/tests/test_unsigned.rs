@@ -0,0 +1,57 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::tests::*;
+use crate::UnsignedMessage;
+
+#[test]
+fn test_offline_signing() {
+    let ton = TonClient::default().unwrap();
+    let keys = ton.crypto.generate_ed25519_keys().unwrap();
+
+    // online machine
+    let unsigned = ton.contracts.create_unsigned_run_message(
+        &WALLET_ADDRESS,
+        WALLET_ABI.to_string().into(),
+        "createOperationLimit",
+        None,
+        json!({ "value": 123 }).into(),
+        None,
+    ).unwrap();
+    let pending = serde_json::to_string(&unsigned).unwrap();
+
+    // air-gapped machine
+    let unsigned: UnsignedMessage = serde_json::from_str(&pending).unwrap();
+    let signature = ton.crypto.sign_detached(&unsigned.bytes_to_sign, &keys).unwrap();
+    assert_eq!(signature.len(), 64);
+
+    // online machine
+    let message = ton.contracts.attach_signature(&unsigned, &signature, Some(&keys.public)).unwrap();
+
+    assert_eq!(message.address, *WALLET_ADDRESS);
+    assert_eq!(message.expire, unsigned.expire);
+
+    assert!(!message.message_id.is_empty());
+}
+
+#[test]
+fn test_unsigned_deploy() {
+    let ton = create_client();
+    let keys = ton.crypto.generate_ed25519_keys().unwrap();
+
+    let params = crate::DeployParams::new(HELLO_ABI.to_string().into(), &HELLO_IMAGE, json!({}).into());
+    let unsigned = ton.contracts.create_unsigned_deploy_message(&params, &keys.public, None).unwrap();
+
+    let address = ton.contracts.get_deploy_address(
+        HELLO_ABI.to_string().into(), &HELLO_IMAGE, None, &keys.public, 0
+    ).unwrap();
+    assert_eq!(unsigned.address, address);
+
+    get_grams_from_giver(&ton, &address, None);
+
+    let signature = ton.crypto.sign_detached(&unsigned.bytes_to_sign, &keys).unwrap();
+    let message = ton.contracts.attach_signature(&unsigned, &signature, Some(&keys.public)).unwrap();
+
+    ton.contracts.process_message(message, None, None, false).unwrap();
+}
@@ -0,0 +1,148 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{Cell, CellBuilder, Ed25519KeyPair, Grams, JsonValue, ResultOfRun, TonAddress, TonContracts};
+use crate::error::*;
+use crate::multisig::submit_transaction_input;
+
+// text comment body starts with zero 32-bit operation code
+const COMMENT_OP: u32 = 0;
+
+/// Build text comment message body. Comment bytes not fitting into the root cell are stored
+/// in the chain of references
+pub fn encode_comment(comment: &str) -> TonResult<Cell> {
+    let bytes = comment.as_bytes();
+    let root_capacity = (crate::MAX_DATA_BITS - 32) / 8;
+    let (root_bytes, mut rest) = bytes.split_at(std::cmp::min(root_capacity, bytes.len()));
+
+    let mut chunks = vec![];
+    while !rest.is_empty() {
+        let (chunk, tail) = rest.split_at(std::cmp::min(crate::MAX_DATA_BITS / 8, rest.len()));
+        chunks.push(chunk);
+        rest = tail;
+    }
+
+    let mut next: Option<Cell> = None;
+    for chunk in chunks.into_iter().rev() {
+        let mut builder = CellBuilder::new();
+        builder.append_bytes(chunk)?;
+        if let Some(cell) = next {
+            builder.append_reference(cell)?;
+        }
+        next = Some(builder.build()?);
+    }
+
+    let mut builder = CellBuilder::new();
+    builder.append_u32(COMMENT_OP)?;
+    builder.append_bytes(root_bytes)?;
+    if let Some(cell) = next {
+        builder.append_reference(cell)?;
+    }
+    builder.build()
+}
+
+/// Read text comment from message body. Returns `None` if body is not a text comment
+pub fn decode_comment(body: &Cell) -> Option<String> {
+    let mut slice = body.slice();
+    if slice.get_u32().ok()? != COMMENT_OP {
+        return None;
+    }
+
+    let mut bytes = vec![];
+    loop {
+        if slice.remaining_bits() % 8 != 0 {
+            return None;
+        }
+        bytes.extend_from_slice(&slice.get_bytes(slice.remaining_bits() / 8).ok()?);
+        if slice.remaining_references() == 0 {
+            break;
+        }
+        slice = slice.get_reference().ok()?.slice();
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Token transfer parameters for `TonContracts::transfer` and `MultisigWallet::submit_transaction`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transfer {
+    pub dest: TonAddress,
+    pub amount: Grams,
+    pub bounce: bool,
+    /// Send all wallet balance instead of `amount`. Supported by multisig wallets only
+    pub all_balance: bool,
+    /// Message body. Supported by multisig wallets only
+    pub payload: Option<Cell>,
+}
+
+impl Transfer {
+    /// Transfer of `amount` nanograms. Bounce flag is set by default
+    pub fn new(dest: TonAddress, amount: Grams) -> Self {
+        Transfer { dest, amount, bounce: true, all_balance: false, payload: None }
+    }
+
+    pub fn bounce(mut self, bounce: bool) -> Self {
+        self.bounce = bounce;
+        self
+    }
+
+    pub fn all_balance(mut self, all_balance: bool) -> Self {
+        self.all_balance = all_balance;
+        self
+    }
+
+    pub fn payload(mut self, payload: Cell) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+
+    /// Attach text comment encoded with `encode_comment` as payload
+    pub fn comment(self, comment: &str) -> TonResult<Self> {
+        Ok(self.payload(encode_comment(comment)?))
+    }
+}
+
+impl TonContracts {
+    /// Send tokens from wallet contract.
+    ///
+    /// Supports wallets with `sendTransaction(dest, value, bounce)` function (bundled `Wallet` and
+    /// `LimitWallet`) and multisig wallets with `submitTransaction` function. Payload (e.g. comment)
+    /// can be attached only to multisig transfers. Multisig wallets requiring several confirmations
+    /// create pending transaction which is executed after other custodians confirm it
+    pub fn transfer(
+        &self,
+        from_wallet: &TonAddress,
+        abi: JsonValue,
+        keys: &Ed25519KeyPair,
+        transfer: &Transfer,
+    ) -> TonResult<ResultOfRun> {
+        let (function_name, input) = transfer_input(&abi.clone().to_value()?, transfer)?;
+        self.run(from_wallet, abi, function_name, None, input.into(), Some(keys))
+    }
+}
+
+pub(crate) fn transfer_input(
+    abi: &serde_json::Value,
+    transfer: &Transfer,
+) -> TonResult<(&'static str, serde_json::Value)> {
+    let has_function = |name: &str| abi["functions"].as_array()
+        .map(|functions| functions.iter().any(|function| function["name"] == name))
+        .unwrap_or(false);
+
+    if has_function("submitTransaction") {
+        Ok(("submitTransaction", submit_transaction_input(transfer)?))
+    } else if has_function("sendTransaction") {
+        if transfer.payload.is_some() || transfer.all_balance {
+            return Err(TonErrorKind::InvalidArg(
+                "Wallet `sendTransaction` function supports neither payload nor all balance transfer".to_owned()).into());
+        }
+        Ok(("sendTransaction", serde_json::json!({
+            "dest": transfer.dest.to_string(),
+            "value": transfer.amount,
+            "bounce": transfer.bounce,
+        })))
+    } else {
+        Err(TonErrorKind::InvalidArg(
+            "ABI has neither `sendTransaction` nor `submitTransaction` function".to_owned()).into())
+    }
+}
@@ -0,0 +1,346 @@
+/*
+ * Copyright 2018-2020 TON DEV SOLUTIONS LTD.
+ */
+
+use crate::{Cell, I256, JsonValue, ResultOfLocalRun, TonAddress, TonContracts, U256};
+use crate::abi_input::parse_integer;
+use crate::error::*;
+use serde_json::Value;
+use std::convert::TryFrom;
+
+/// TVM stack value used as `run_get` input and output.
+///
+/// In JSON representation of core integers are decimal or `0x` prefixed hex strings, tuples are
+/// arrays, cells, slices and builders are objects like `{ "type": "Cell", "value": "<base64 BOC>" }`.
+/// Lists are stored in TVM as nested pairs `[item, [item, null]]` (cons-lists) and are unrolled
+/// into `List` values by `from_json`.
+///
+/// Note that cons-list can't be distinguished from tuple by its JSON: any pair which second item
+/// is `null` or another such pair is decoded as `List`, e.g. `(int, null)` tuple becomes
+/// one-element list. Use `from_json_tuple` to keep all arrays as tuples
+#[derive(Clone, Debug, PartialEq)]
+pub enum TvmValue {
+    /// TVM 257-bit signed integer stored as sign and magnitude
+    Integer { negative: bool, magnitude: U256 },
+    Cell(Cell),
+    Slice(Cell),
+    Builder(Cell),
+    Tuple(Vec<TvmValue>),
+    List(Vec<TvmValue>),
+    Null,
+}
+
+impl TvmValue {
+    /// Parse JSON stack value unrolling cons-lists into `List` values
+    pub fn from_json(value: &Value) -> TonResult<TvmValue> {
+        Self::parse_json(value, true)
+    }
+
+    /// Parse JSON stack value keeping all arrays as `Tuple` values. Cons-lists can be converted
+    /// later with `cons_to_vec` if value layout is known
+    pub fn from_json_tuple(value: &Value) -> TonResult<TvmValue> {
+        Self::parse_json(value, false)
+    }
+
+    fn parse_json(value: &Value, unroll_lists: bool) -> TonResult<TvmValue> {
+        let parse_items = |items: Vec<&Value>| items.into_iter()
+            .map(|item| Self::parse_json(item, unroll_lists))
+            .collect::<TonResult<Vec<_>>>();
+        match value {
+            Value::Null => Ok(TvmValue::Null),
+            Value::Number(number) => TvmValue::integer_from_str(&number.to_string()),
+            Value::String(string) => TvmValue::integer_from_str(string),
+            Value::Array(items) => {
+                if unroll_lists && is_cons_list(value) {
+                    parse_items(cons_items(value)).map(TvmValue::List)
+                } else {
+                    parse_items(items.iter().collect()).map(TvmValue::Tuple)
+                }
+            }
+            Value::Object(object) => {
+                let cell = || -> TonResult<Cell> {
+                    let boc = object.get("value").and_then(|value| value.as_str())
+                        .ok_or_else(|| TonErrorKind::InvalidData(format!("Invalid TVM cell value: {}", value)))?;
+                    Cell::from_boc(&base64::decode(boc)?)
+                };
+                match object.get("type").and_then(|kind| kind.as_str()) {
+                    Some("Cell") => Ok(TvmValue::Cell(cell()?)),
+                    Some("Slice") => Ok(TvmValue::Slice(cell()?)),
+                    Some("Builder") => Ok(TvmValue::Builder(cell()?)),
+                    Some("List") => {
+                        let items = object.get("value").and_then(|value| value.as_array())
+                            .ok_or_else(|| TonErrorKind::InvalidData(format!("Invalid TVM list value: {}", value)))?;
+                        parse_items(items.iter().collect()).map(TvmValue::List)
+                    }
+                    _ => Err(TonErrorKind::InvalidData(format!("Unsupported TVM stack value: {}", value)).into())
+                }
+            }
+            Value::Bool(_) => Err(TonErrorKind::InvalidData(format!("Unsupported TVM stack value: {}", value)).into())
+        }
+    }
+
+    /// JSON representation accepted by core. Lists are converted into cons-lists
+    pub fn to_json(&self) -> TonResult<Value> {
+        let cell = |kind: &str, cell: &Cell| -> TonResult<Value> {
+            Ok(serde_json::json!({
+                "type": kind,
+                "value": base64::encode(&cell.to_boc()?),
+            }))
+        };
+        Ok(match self {
+            TvmValue::Integer { negative, magnitude } =>
+                Value::String(format!("{}{:#x}", if *negative { "-" } else { "" }, magnitude)),
+            TvmValue::Cell(value) => cell("Cell", value)?,
+            TvmValue::Slice(value) => cell("Slice", value)?,
+            TvmValue::Builder(value) => cell("Builder", value)?,
+            TvmValue::Tuple(items) => Value::Array(items.iter().map(TvmValue::to_json).collect::<TonResult<_>>()?),
+            TvmValue::List(items) => vec_to_cons(items.iter().map(TvmValue::to_json).collect::<TonResult<_>>()?),
+            TvmValue::Null => Value::Null,
+        })
+    }
+
+    fn integer_from_str(string: &str) -> TonResult<TvmValue> {
+        parse_integer(string)
+            .and_then(|(negative, magnitude)| U256::from_be_slice(&magnitude)
+                .map(|magnitude| TvmValue::Integer { negative: negative && !magnitude.is_zero(), magnitude }))
+            .ok_or_else(|| TonErrorKind::InvalidData(format!("Invalid TVM integer: {}", string)).into())
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == TvmValue::Null
+    }
+
+    pub fn as_u256(&self) -> TonResult<U256> {
+        match self {
+            TvmValue::Integer { negative: false, magnitude } => Ok(*magnitude),
+            _ => Err(self.unexpected("unsigned integer"))
+        }
+    }
+
+    pub fn as_i256(&self) -> TonResult<I256> {
+        match self {
+            TvmValue::Integer { negative, magnitude } => I256::from_sign_magnitude(*negative, *magnitude)
+                .ok_or_else(|| TonErrorKind::InvalidData(format!("Integer exceeds int256: {:?}", self)).into()),
+            _ => Err(self.unexpected("integer"))
+        }
+    }
+
+    pub fn as_u64(&self) -> TonResult<u64> {
+        u64::try_from(self.as_u256()?)
+    }
+
+    pub fn as_u128(&self) -> TonResult<u128> {
+        u128::try_from(self.as_u256()?)
+    }
+
+    pub fn as_i64(&self) -> TonResult<i64> {
+        i64::try_from(self.as_i256()?)
+    }
+
+    pub fn as_bool(&self) -> TonResult<bool> {
+        // TVM represents `true` as -1 and `false` as 0
+        match self {
+            TvmValue::Integer { magnitude, .. } => Ok(!magnitude.is_zero()),
+            _ => Err(self.unexpected("boolean integer"))
+        }
+    }
+
+    /// Cell of `Cell`, `Slice` or `Builder` value
+    pub fn as_cell(&self) -> TonResult<&Cell> {
+        match self {
+            TvmValue::Cell(cell) | TvmValue::Slice(cell) | TvmValue::Builder(cell) => Ok(cell),
+            _ => Err(self.unexpected("cell"))
+        }
+    }
+
+    /// Address stored in slice
+    pub fn as_address(&self) -> TonResult<TonAddress> {
+        match self {
+            TvmValue::Slice(cell) => cell.slice().get_address()?
+                .ok_or_else(|| TonErrorKind::InvalidData("Slice contains empty address".to_owned()).into()),
+            _ => Err(self.unexpected("slice"))
+        }
+    }
+
+    /// Items of `Tuple` or `List`
+    pub fn as_items(&self) -> TonResult<&[TvmValue]> {
+        match self {
+            TvmValue::Tuple(items) | TvmValue::List(items) => Ok(items),
+            _ => Err(self.unexpected("tuple or list"))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> TonError {
+        TonErrorKind::InvalidData(format!("Expected TVM {}, got {:?}", expected, self)).into()
+    }
+}
+
+fn cons_items(cons: &Value) -> Vec<&Value> {
+    let mut items = vec![];
+    let mut item = cons;
+    while !item.is_null() {
+        items.push(&item[0]);
+        item = &item[1];
+    }
+    items
+}
+
+/// Cons-list is a pair which second item is `null` or another cons-list
+pub(crate) fn is_cons_list(value: &Value) -> bool {
+    let mut item = value;
+    loop {
+        match item.as_array() {
+            Some(pair) if pair.len() == 2 => item = &pair[1],
+            _ => return false
+        }
+        if item.is_null() {
+            return true;
+        }
+    }
+}
+
+/// Convert cons-list `[item, [item, null]]` into array of items. Items are not converted
+pub fn cons_to_vec(cons: Value) -> TonResult<Value> {
+    let mut result = vec![];
+    let mut item = cons;
+    while !item.is_null() {
+        if item.as_array().map(|pair| pair.len()).unwrap_or(0) != 2 {
+            return Err(TonErrorKind::InvalidArg("Invalid cons".to_owned()).into());
+        }
+        result.push(item[0].take());
+        item = item[1].take();
+    }
+    Ok(result.into())
+}
+
+/// Convert array of items into cons-list accepted by `run_get` as list input
+pub fn vec_to_cons(items: Vec<Value>) -> Value {
+    items.into_iter().rev()
+        .fold(Value::Null, |tail, item| Value::Array(vec![item, tail]))
+}
+
+/// Convert cons-list of tuples, e.g. elector `participant_list`, into vector of tuple items.
+/// Tuple items are not converted, use `flatten_cons` for items holding lists
+pub fn cons_to_tuples(cons: Value) -> TonResult<Vec<Vec<Value>>> {
+    match cons_to_vec(cons)? {
+        Value::Array(items) => items.into_iter()
+            .map(|item| match item {
+                Value::Array(fields) => Ok(fields),
+                item => Err(TonErrorKind::InvalidArg(format!("Cons-list item is not a tuple: {}", item)).into())
+            })
+            .collect(),
+        _ => Ok(vec![])
+    }
+}
+
+/// Recursively convert cons-lists in `value` into arrays, so list of tuples containing
+/// other lists becomes array of arrays. List items are kept as tuples and only their items are
+/// converted, so `[key, value]` entries are not mistaken for lists. Note that other pairs which
+/// second item is `null` or another cons-list are treated as lists
+pub fn flatten_cons(value: Value) -> Value {
+    match value {
+        Value::Array(_) if is_cons_list(&value) =>
+            Value::Array(cons_items(&value).into_iter().cloned().map(flatten_list_item).collect()),
+        Value::Array(items) => Value::Array(items.into_iter().map(flatten_cons).collect()),
+        value => value
+    }
+}
+
+fn flatten_list_item(item: Value) -> Value {
+    match item {
+        Value::Array(items) => Value::Array(items.into_iter().map(flatten_cons).collect()),
+        item => item
+    }
+}
+
+/// Recognize dictionary-like structure: list or tuple of `[key, value]` pairs with unique
+/// integer keys, e.g. elector `participant_list`. Returns entries in original order with keys
+/// and values as is. Keys are compared by value, so `"0x1"` and `"1"` are duplicates
+pub fn as_dictionary(value: &Value) -> Option<Vec<(String, Value)>> {
+    // tuple of pairs can look like cons-list, so both layouts are checked
+    if is_cons_list(value) {
+        if let Some(entries) = dictionary_entries(cons_items(value)) {
+            return Some(entries);
+        }
+    }
+    dictionary_entries(value.as_array()?.iter().collect())
+}
+
+fn dictionary_entries(items: Vec<&Value>) -> Option<Vec<(String, Value)>> {
+    let mut keys = std::collections::HashSet::new();
+    let mut entries = vec![];
+    for item in items {
+        let pair = item.as_array().filter(|pair| pair.len() == 2)?;
+        let key = match &pair[0] {
+            Value::String(key) => key.clone(),
+            Value::Number(key) if key.is_u64() || key.is_i64() => key.to_string(),
+            _ => return None
+        };
+        let (negative, magnitude) = parse_integer(&key)?;
+        let magnitude: Vec<u8> = magnitude.into_iter().skip_while(|byte| *byte == 0).collect();
+        if !keys.insert((negative && !magnitude.is_empty(), magnitude)) {
+            return None;
+        }
+        entries.push((key, pair[1].clone()));
+    }
+    Some(entries)
+}
+
+impl From<u64> for TvmValue {
+    fn from(value: u64) -> Self {
+        TvmValue::from(U256::from(value))
+    }
+}
+
+impl From<i64> for TvmValue {
+    fn from(value: i64) -> Self {
+        TvmValue::from(I256::from(value))
+    }
+}
+
+impl From<U256> for TvmValue {
+    fn from(value: U256) -> Self {
+        TvmValue::Integer { negative: false, magnitude: value }
+    }
+}
+
+impl From<I256> for TvmValue {
+    fn from(value: I256) -> Self {
+        TvmValue::Integer { negative: value.is_negative(), magnitude: value.unsigned_abs() }
+    }
+}
+
+impl From<bool> for TvmValue {
+    fn from(value: bool) -> Self {
+        TvmValue::from(if value { -1i64 } else { 0 })
+    }
+}
+
+impl From<Cell> for TvmValue {
+    fn from(value: Cell) -> Self {
+        TvmValue::Cell(value)
+    }
+}
+
+impl TonContracts {
+    /// Run get-method with typed stack values. Cons-lists in output are unrolled into `List` values
+    pub fn run_get_values(
+        &self,
+        address: Option<&TonAddress>,
+        account: Option<JsonValue>,
+        function_name: &str,
+        input: &[TvmValue],
+    ) -> TonResult<Vec<TvmValue>> {
+        let input = if input.is_empty() {
+            None
+        } else {
+            Some(Value::Array(input.iter().map(TvmValue::to_json).collect::<TonResult<_>>()?).into())
+        };
+        let result: ResultOfLocalRun = self.run_get(address, account, function_name, input)?;
+        match &result.output {
+            Value::Array(stack) => stack.iter().map(TvmValue::from_json).collect(),
+            Value::Null => Ok(vec![]),
+            output => Err(TonErrorKind::InvalidData(format!("Invalid get-method output: {}", output)).into())
+        }
+    }
+}